@@ -0,0 +1,132 @@
+//! blueprints drawn as ascii art, the other way around from [`World::render`].
+//!
+//! the drawing comes first, one character per tile, using each kind's
+//! [symbol](StructureKind::symbol) and `.` (or a space) for nothing. a digit can stand in for
+//! any tile of a structure other than its top left corner, which gives the structure that digit
+//! as its name. a 1x1 structure like single storage doesn't have another tile, so its digit goes
+//! on the empty tile right after it instead, like `O4`. after a blank line, the rest is a
+//! [blueprint file](crate::library), where those digits are used to refer to structures:
+//! ```text
+//! PPR1RRRR.s
+//! PPRRRRRR.2
+//! .........s
+//!
+//! 1.0 -> 2.0
+//! input air = 1.0
+//! output a = 2.0
+//! output b = 2.1
+//! ```
+//! the blueprint is as big as the drawing, unless it says otherwise with `size`.
+//!
+//! there are only ten digits, so at most ten structures can have names. the rest can still be
+//! drawn, they just can't be connected or exposed as ports.
+
+use std::io;
+
+use crate::{library::NamedBlueprint, prelude::*, script::Interpreter};
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// builds the blueprint an ascii drawing describes.
+pub fn parse(source: &str) -> io::Result<NamedBlueprint> {
+    let mut lines = source.lines();
+    let mut grid: Vec<Vec<char>> = vec![];
+    // which line of the source each row of the grid came from, comments and all
+    let mut line_numbers = vec![];
+    // blanked out, so line numbers in errors from the rest still line up
+    let mut rest = String::new();
+    for (number, line) in lines.by_ref().enumerate() {
+        rest.push('\n');
+        let line = line.trim_end();
+        if line.trim_start().starts_with('#') {
+            continue;
+        }
+        if line.is_empty() {
+            break;
+        }
+        grid.push(line.chars().collect());
+        line_numbers.push(number + 1);
+    }
+    for line in lines {
+        rest.push_str(line);
+        rest.push('\n');
+    }
+
+    let width = grid.iter().map(Vec::len).max().unwrap_or(0);
+    let height = grid.len();
+    let cell = |x: usize, y: usize| grid[y].get(x).copied().unwrap_or('.');
+    let mut claimed = vec![vec![false; width]; height];
+    let mut interpreter = Interpreter::default();
+
+    // going top to bottom, left to right, the first tile of a structure
+    // we haven't seen yet is always its top left corner.
+    for y in 0..height {
+        for x in 0..width {
+            let symbol = cell(x, y);
+            if claimed[y][x] || matches!(symbol, '.' | ' ') {
+                continue;
+            }
+            let at = format!("line {}, column {}", line_numbers[y], x + 1);
+            let kind = StructureKind::from_symbol(symbol).ok_or_else(|| {
+                invalid(match symbol.is_ascii_digit() {
+                    true => format!("{at}: {symbol} isn't on top of a structure's tile"),
+                    false => format!("{at}: no structure is drawn as {symbol:?}"),
+                })
+            })?;
+            let size = kind.size();
+            let (w, h) = (size.w as usize, size.h as usize);
+            if x + w > width || y + h > height {
+                return Err(invalid(format!(
+                    "{at}: {kind:?} doesn't fit in the drawing"
+                )));
+            }
+            let mut name = None;
+            for dy in 0..h {
+                for dx in 0..w {
+                    let (cx, cy) = (x + dx, y + dy);
+                    let c = cell(cx, cy);
+                    if claimed[cy][cx] || !(c == symbol || c.is_ascii_digit()) {
+                        return Err(invalid(format!(
+                            "{at}: {kind:?} is {}x{}, but line {}, column {} is {c:?}",
+                            size.w,
+                            size.h,
+                            line_numbers[cy],
+                            cx + 1
+                        )));
+                    }
+                    claimed[cy][cx] = true;
+                    if c.is_ascii_digit() {
+                        if name.is_some_and(|name| name != c) {
+                            return Err(invalid(format!("{at}: {kind:?} has more than one digit")));
+                        }
+                        name = Some(c);
+                    }
+                }
+            }
+            // the empty tile after a 1x1 structure. it can't be the corner of anything else,
+            // since digits never are.
+            if (w, h) == (1, 1) && x + 1 < width && !claimed[y][x + 1] {
+                let c = cell(x + 1, y);
+                if c.is_ascii_digit() {
+                    claimed[y][x + 1] = true;
+                    name = Some(c);
+                }
+            }
+            let structure = interpreter.world.place(kind, x as Coord, y as Coord);
+            if let Some(name) = name {
+                let name = name.to_string();
+                if interpreter.names.insert(name.clone(), structure).is_some() {
+                    return Err(invalid(format!("{at}: {name} is used more than once")));
+                }
+            }
+        }
+    }
+
+    let size = Size {
+        w: width as Coord,
+        h: height as Coord,
+    };
+    NamedBlueprint::parse_with(interpreter, Some(size), &rest)
+}
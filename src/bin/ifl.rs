@@ -0,0 +1,111 @@
+//! command line access to the library, for people who'd rather not write rust.
+
+use std::{
+    env,
+    fs::File,
+    io::{self, BufReader, BufWriter, Write},
+    process::ExitCode,
+};
+
+use immortality_factory_laboratory::{market::PriceTable, prelude::*, script};
+
+const USAGE: &str = "\
+usage: ifl <command> <file>
+
+commands:
+    export <script> [save]  run a layout script and write the save file (stdout if not given)
+    merge <save> <script> <x> <y> [out]
+                            paste a layout script into an existing save at (x, y),
+                            keeping everything else in it (stdout if no out given)
+    inspect <save>          list machines and connections
+    render <save>           draw the machines as ascii
+    stats <save>            count machines and measure wires";
+
+fn load(path: &str) -> io::Result<World> {
+    World::import(&mut BufReader::new(File::open(path)?))
+}
+
+fn export(script_path: &str, save_path: Option<&str>) -> io::Result<()> {
+    let world = script::parse(&std::fs::read_to_string(script_path)?)?;
+    match save_path {
+        Some(path) => {
+            let mut f = BufWriter::new(File::create(path)?);
+            world.export(&mut f)?;
+            f.flush()
+        }
+        None => world.export(&mut io::stdout().lock()),
+    }
+}
+
+fn merge(
+    save_path: &str,
+    script_path: &str,
+    x: &str,
+    y: &str,
+    out: Option<&str>,
+) -> io::Result<()> {
+    let coord = |c: &str| {
+        c.parse::<Coord>().map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidInput, format!("bad coordinate {c:?}"))
+        })
+    };
+    let (x, y) = (coord(x)?, coord(y)?);
+    let mut save = Save::import(&mut BufReader::new(File::open(save_path)?))?;
+    let world = script::parse(&std::fs::read_to_string(script_path)?)?;
+    save.paste(&world, x, y)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    match out {
+        Some(path) => {
+            let mut f = BufWriter::new(File::create(path)?);
+            save.export(&mut f)?;
+            f.flush()
+        }
+        None => save.export(&mut io::stdout().lock()),
+    }
+}
+
+fn stats(world: &World) {
+    println!("structures: {}", world.structures.len());
+    println!("connections: {}", world.connection_count());
+    println!("wiring: {}", world.wire_stats());
+    for (kind, count) in world.kind_counts() {
+        println!("    {kind:?}: {count}");
+    }
+    // no build cost: the game's cost lists repeat items with falling amounts, and even an
+    // air pump lists late game bars, so they aren't the price of one machine. until someone
+    // works out what they are, adding them up would just be making numbers up.
+    // there are no built in prices, so this just says what each market sells
+    let market = world.market_income(&PriceTable::new());
+    if !market.markets.is_empty() {
+        println!("markets:");
+        for line in market.to_string().lines() {
+            println!("    {line}");
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let result = match args.as_slice() {
+        ["export", script] => export(script, None),
+        ["export", script, save] => export(script, Some(save)),
+        ["merge", save, script, x, y] => merge(save, script, x, y, None),
+        ["merge", save, script, x, y, out] => merge(save, script, x, y, Some(out)),
+        ["inspect", save] => load(save).map(|world| print!("{}", world.describe())),
+        ["render", save] => load(save).map(|world| print!("{}", world.render())),
+        ["stats", save] => load(save).map(|world| stats(&world)),
+        _ => return Err(USAGE.to_owned()),
+    };
+    result.map_err(|e| format!("error: {e}"))
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{message}");
+            ExitCode::FAILURE
+        }
+    }
+}
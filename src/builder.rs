@@ -0,0 +1,215 @@
+//! structures with things already in them, ready to be [placed](World::place).
+//! ```
+//! # use immortality_factory_laboratory::{builder, prelude::*};
+//! let mut world = World::new();
+//! let unifier = builder::Unifier::new().input(0, GloomShard).output(EqualizedOrb);
+//! world.place(unifier, 0, 0);
+//! ```
+//! kinds with one input (or output) take just the item, kinds with more also take the port.
+//!
+//! these are named after the kinds they build, so they're left out of the prelude
+//! to keep from clashing with [`StructureKind`]'s variants. use them as `builder::StorageVault`.
+
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
+use crate::{prelude::*, recipe::Recipe, structure::StructureDataFull};
+
+/// more items than a structure has storage slots for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StorageOverflow {
+    pub kind: StructureKind,
+    pub capacity: usize,
+}
+
+impl Display for StorageOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} only has room for {} items, tried to put in more",
+            self.kind, self.capacity
+        )
+    }
+}
+
+impl Error for StorageOverflow {}
+
+macro_rules! builders {
+    ($($kind:ident),* $(,)?) => {$(
+        #[doc = concat!("builds a [`StructureKind::", stringify!($kind), "`].")]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $kind(StructureDataFull);
+
+        impl $kind {
+            /// an empty one.
+            pub fn new() -> Self {
+                Self(StructureKind::$kind.into())
+            }
+
+            /// fills in the input and output slots from `recipe`.
+            /// panics if it's for a different kind.
+            pub fn recipe(mut self, recipe: &Recipe) -> Self {
+                recipe.apply(&mut self.0);
+                self
+            }
+        }
+
+        impl Default for $kind {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl From<$kind> for StructureDataFull {
+            fn from(value: $kind) -> Self {
+                value.0
+            }
+        }
+
+        impl HasSize for $kind {
+            fn size(&self) -> Size {
+                self.0.size()
+            }
+        }
+
+        impl Placeable for $kind {
+            type Id = Structure;
+
+            fn place_in(self, world: &mut World, x: Coord, y: Coord) -> Self::Id {
+                self.0.place_in(world, x, y)
+            }
+        }
+    )*};
+}
+
+builders!(
+    AirPump,
+    Refinery,
+    Disharmonizer,
+    Unifier,
+    SubdimensionalMarket,
+    Splitter,
+    Merger,
+    StorageVault,
+    AbysalDoor,
+    SingleStorage,
+    Laboratory,
+    RitualInfuser,
+    BigMerger,
+    BigSplitter,
+);
+
+macro_rules! slots {
+    ($slots:ident, $method:ident, one: $($kind:ident),* $(,)?) => {$(
+        impl $kind {
+            pub fn $method(mut self, item: Item) -> Self {
+                self.0.$slots()[0].item = item;
+                self
+            }
+        }
+    )*};
+    ($slots:ident, $method:ident, many: $($kind:ident),* $(,)?) => {$(
+        impl $kind {
+            /// panics if there's no such port.
+            pub fn $method(mut self, port: usize, item: Item) -> Self {
+                let slot = self.0.$slots().get_mut(port).unwrap_or_else(|| {
+                    panic!(
+                        "Tried to set {:?} {} #{port}, does not exist.",
+                        StructureKind::$kind,
+                        stringify!($method)
+                    )
+                });
+                slot.item = item;
+                self
+            }
+        }
+    )*};
+}
+
+slots!(
+    get_inputs_mut,
+    input,
+    one: Refinery,
+    Disharmonizer,
+    SubdimensionalMarket,
+    Splitter,
+    StorageVault,
+    AbysalDoor,
+    SingleStorage,
+    Laboratory,
+    BigSplitter,
+);
+slots!(
+    get_inputs_mut,
+    input,
+    many: Unifier,
+    Merger,
+    RitualInfuser,
+    BigMerger,
+);
+// the infuser's output isn't in the save, so there's nothing to set
+slots!(
+    get_outputs_mut,
+    output,
+    one: AirPump,
+    Refinery,
+    Unifier,
+    Merger,
+    StorageVault,
+    BigMerger,
+);
+slots!(
+    get_outputs_mut,
+    output,
+    many: Disharmonizer,
+    SubdimensionalMarket,
+    Splitter,
+    BigSplitter,
+);
+
+macro_rules! storage {
+    ($($kind:ident),* $(,)?) => {$(
+        impl $kind {
+            /// the first `count` storage slots hold `item`, the rest are empty.
+            pub fn filled(item: Item, count: usize) -> Result<Self, StorageOverflow> {
+                Self::new().with_storage(std::iter::repeat_n(item, count))
+            }
+
+            /// panics if there's no such slot.
+            pub fn storage(mut self, slot: usize, item: Item) -> Self {
+                let storage = self.0.get_storage_mut();
+                let len = storage.len();
+                *storage.get_mut(slot).unwrap_or_else(|| {
+                    panic!(
+                        "Tried to set {:?} storage #{slot}, only {len} exist.",
+                        StructureKind::$kind
+                    )
+                }) = item;
+                self
+            }
+
+            /// fills storage slots in order, leaving the rest as they were.
+            /// nothing changes if there are more items than slots.
+            /// only takes one past the last slot to find out, so endless iterators are fine.
+            pub fn with_storage(
+                mut self,
+                items: impl IntoIterator<Item = Item>,
+            ) -> Result<Self, StorageOverflow> {
+                let storage = self.0.get_storage_mut();
+                let items: Vec<Item> = items.into_iter().take(storage.len() + 1).collect();
+                if items.len() > storage.len() {
+                    return Err(StorageOverflow {
+                        kind: StructureKind::$kind,
+                        capacity: storage.len(),
+                    });
+                }
+                storage[..items.len()].copy_from_slice(&items);
+                Ok(self)
+            }
+        }
+    )*};
+}
+
+storage!(Refinery, StorageVault);
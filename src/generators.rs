@@ -0,0 +1,420 @@
+//! blueprints built from a few numbers instead of by hand.
+
+use std::{collections::HashMap, hash::Hash};
+
+use crate::prelude::*;
+
+/// something that builds blueprints to order, so one definition covers every size of a module.
+/// ```
+/// # use immortality_factory_laboratory::{generators::*, prelude::*};
+/// /// refineries one after the other, taking in some item.
+/// struct RefineryLine;
+///
+/// impl BlueprintGenerator for RefineryLine {
+///     /// how many, and what goes in.
+///     type Params = (usize, Item);
+///
+///     fn generate(&self, &(count, item): &(usize, Item)) -> Blueprint {
+///         let mut contents = World::new();
+///         let refineries: Vec<_> = (0..count)
+///             .map(|i| contents.place(Refinery, 7 * i as Coord, 0))
+///             .collect();
+///         for pair in refineries.windows(2) {
+///             contents.connect(pair[0].output(0), pair[1].input(0));
+///         }
+///         contents.structures[0].structure.get_inputs_mut()[0].item = item;
+///         Blueprint {
+///             contents,
+///             size: Size { w: 7 * count as Coord - 1, h: 2 },
+///             inputs: vec![refineries[0].input(0)],
+///             outputs: vec![refineries[count - 1].output(0)],
+///         }
+///     }
+/// }
+///
+/// let mut lines = GeneratorCache::new(RefineryLine);
+/// let mut world = World::new();
+/// for row in 0..4 {
+///     // only built once
+///     world.place(lines.get(&(3, GloomShard)), 0, 3 * row);
+/// }
+/// assert_eq!(lines.len(), 1);
+/// ```
+pub trait BlueprintGenerator {
+    /// whatever it takes to tell two blueprints apart, like how many machines to use
+    /// or what item to feed them.
+    type Params: Clone + Eq + Hash;
+
+    fn generate(&self, params: &Self::Params) -> Blueprint;
+}
+
+/// a generator that only builds each blueprint once.
+pub struct GeneratorCache<G: BlueprintGenerator> {
+    generator: G,
+    blueprints: HashMap<G::Params, Blueprint>,
+}
+
+impl<G: BlueprintGenerator> GeneratorCache<G> {
+    pub fn new(generator: G) -> Self {
+        Self {
+            generator,
+            blueprints: HashMap::new(),
+        }
+    }
+
+    /// the blueprint for `params`, generated the first time it's asked for.
+    pub fn get(&mut self, params: &G::Params) -> &Blueprint {
+        if !self.blueprints.contains_key(params) {
+            let blueprint = self.generator.generate(params);
+            self.blueprints.insert(params.clone(), blueprint);
+        }
+        &self.blueprints[params]
+    }
+
+    pub fn generator(&self) -> &G {
+        &self.generator
+    }
+
+    /// how many blueprints have been generated.
+    pub fn len(&self) -> usize {
+        self.blueprints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blueprints.is_empty()
+    }
+
+    /// forgets every blueprint, so they get generated again.
+    pub fn clear(&mut self) {
+        self.blueprints.clear();
+    }
+}
+
+/// how many outputs (or inputs) each machine of a tree gets, in heap order:
+/// machine `i` feeds machines `5i + 1` to `5i + 5`, if there are that many.
+///
+/// every machine but the last is a full 5, so `n` ends takes the fewest machines possible,
+/// `ceil((n - 1) / 4)`, with the leftover 2 to 5 on the last one.
+fn fans(n: usize) -> Vec<usize> {
+    assert!(n >= 2, "A tree needs at least 2 ends, got {n}.");
+    let machines = (n - 1).div_ceil(4);
+    let mut fans = vec![5; machines];
+    fans[machines - 1] = n + 4 - 4 * machines;
+    fans
+}
+
+fn depth(mut node: usize) -> Coord {
+    let mut depth = 0;
+    while node > 0 {
+        node = (node - 1) / 5;
+        depth += 1;
+    }
+    depth
+}
+
+struct Tree {
+    split: bool,
+    fans: Vec<usize>,
+    /// how far from the trunk the deepest machine is.
+    depth: Coord,
+    world: World,
+    ends_in: Vec<PortIn>,
+    ends_out: Vec<PortOut>,
+}
+
+impl Tree {
+    fn kind(&self, node: usize) -> StructureKind {
+        match (self.split, self.fans[node]) {
+            (true, 2) => Splitter,
+            (true, _) => BigSplitter,
+            (false, 2) => Merger,
+            (false, _) => BigMerger,
+        }
+    }
+
+    /// places `node` and everything under it from `y` down, going through ends in order.
+    /// returns the machine and how many rows it all took.
+    fn build(&mut self, node: usize, y: Coord) -> (Structure, Coord) {
+        let kind = self.kind(node);
+        let depth = depth(node);
+        // splits go left to right, merges right to left.
+        // a gap between columns so the wires have somewhere to go.
+        let x = 2 * if self.split {
+            depth
+        } else {
+            self.depth - depth
+        };
+        let machine = self.world.place(kind, x, y);
+        let mut rows = 0;
+        for port in 0..self.fans[node] {
+            let child = 5 * node + 1 + port;
+            if child >= self.fans.len() {
+                if self.split {
+                    self.ends_out.push(machine.output(port));
+                } else {
+                    self.ends_in.push(machine.input(port));
+                }
+                continue;
+            }
+            let (child_machine, child_rows) = self.build(child, y + rows);
+            rows += child_rows;
+            if self.split {
+                self.world
+                    .connect(machine.output(port), child_machine.input(0));
+            } else {
+                self.world
+                    .connect(child_machine.output(0), machine.input(port));
+            }
+        }
+        (machine, rows.max(kind.height()))
+    }
+}
+
+fn tree(n: usize, split: bool) -> Blueprint {
+    let fans = fans(n);
+    let mut tree = Tree {
+        split,
+        depth: depth(fans.len() - 1),
+        fans,
+        world: World::new(),
+        ends_in: vec![],
+        ends_out: vec![],
+    };
+    let (trunk, height) = tree.build(0, 0);
+    let (inputs, outputs) = if split {
+        (vec![trunk.input(0)], tree.ends_out)
+    } else {
+        (tree.ends_in, vec![trunk.output(0)])
+    };
+    Blueprint {
+        size: Size {
+            w: 2 * tree.depth + 1,
+            h: height,
+        },
+        contents: tree.world,
+        inputs,
+        outputs,
+    }
+}
+
+/// splits one input into `n` outputs, with as few machines as it gets.
+///
+/// Big Splitters fill their lowest output first, and only pass things further down when it's
+/// backed up. the outputs are numbered in that same order, so output 0 gets first dibs,
+/// then output 1, and so on. put whatever matters most on the low outputs.
+/// if the input keeps up with everything, it doesn't matter and every output gets its fill.
+/// if it doesn't, use a [`balanced_splitter_tree`].
+///
+/// panics if `n` is less than 2.
+pub fn splitter_tree(n: usize) -> Blueprint {
+    tree(n, true)
+}
+
+/// merges `n` inputs into one output, with as few machines as it gets.
+///
+/// Big Mergers take from their lowest input first, so input 0 always goes through,
+/// and the highest inputs only get a turn when there's room left.
+/// same numbering as [`splitter_tree`], just backwards. see [`balanced_merger_tree`] for one
+/// that takes turns.
+///
+/// panics if `n` is less than 2.
+pub fn merger_tree(n: usize) -> Blueprint {
+    tree(n, false)
+}
+
+/// small Splitters and Mergers, assuming they take turns instead of going lowest first.
+struct BinaryTree {
+    n: usize,
+    /// how far right the first column of the tree itself is.
+    x: Coord,
+    /// columns go right to left for merging, so this is where the root goes.
+    root_column: Coord,
+    world: World,
+    ends_in: Vec<PortIn>,
+    ends_out: Vec<PortOut>,
+    /// splitter outputs past the last end, that go back around to the input.
+    loops: Vec<PortOut>,
+}
+
+impl BinaryTree {
+    /// a complete tree with ends `lo..lo + size` (`size` a power of 2) under it, missing any
+    /// branches that are entirely past the last end.
+    fn split(&mut self, lo: usize, size: usize, column: Coord, y: Coord) -> (Structure, Coord) {
+        let machine = self.world.place(Splitter, self.x + 2 * column, y);
+        let mut rows = 0;
+        for port in 0..2 {
+            let lo = lo + port * size / 2;
+            if lo >= self.n {
+                self.loops.push(machine.output(port));
+            } else if size == 2 {
+                self.ends_out.push(machine.output(port));
+            } else {
+                let (child, child_rows) = self.split(lo, size / 2, column + 1, y + rows);
+                rows += child_rows;
+                self.world.connect(machine.output(port), child.input(0));
+            }
+        }
+        (machine, rows.max(Splitter.height()))
+    }
+
+    /// ends `lo..lo + count` under one machine, half on each side, so no end is more than one
+    /// merger deeper than any other.
+    fn merge(&mut self, lo: usize, count: usize, column: Coord, y: Coord) -> (Structure, Coord) {
+        let machine = self
+            .world
+            .place(Merger, self.x + 2 * (self.root_column - column), y);
+        let halves = [(lo, count.div_ceil(2)), (lo + count.div_ceil(2), count / 2)];
+        let mut rows = 0;
+        for (port, (lo, count)) in halves.into_iter().enumerate() {
+            if count == 1 {
+                self.ends_in.push(machine.input(port));
+            } else {
+                let (child, child_rows) = self.merge(lo, count, column + 1, y + rows);
+                rows += child_rows;
+                self.world.connect(child.output(0), machine.input(port));
+            }
+        }
+        (machine, rows.max(Merger.height()))
+    }
+}
+
+/// splits one input into `n` outputs that all get the same share, however little comes in.
+///
+/// it's a tree of small Splitters, which take turns between their outputs, with as many ends
+/// as the next power of 2. the ends past `n` go back around through Mergers at the front, so
+/// whatever comes out of them gets another go. that takes more machines than a
+/// [`splitter_tree`], and the leftover ends keep some items going around in circles.
+///
+/// NOTE: that small Splitters and Mergers take turns is an assumption. nothing in the game
+/// data says so, it's just how [`crate::sim`] models them, and it's only there that these
+/// trees are known to split evenly. if they go lowest first like the big ones, this is just
+/// a bigger [`splitter_tree`].
+///
+/// panics if `n` is less than 2.
+pub fn balanced_splitter_tree(n: usize) -> Blueprint {
+    assert!(n >= 2, "A tree needs at least 2 ends, got {n}.");
+    let size = n.next_power_of_two();
+    let depth = size.trailing_zeros() as Coord;
+    // room for the mergers in front
+    let x = if size == n { 0 } else { 2 };
+    let mut tree = BinaryTree {
+        n,
+        x,
+        root_column: 0,
+        world: World::new(),
+        ends_in: vec![],
+        ends_out: vec![],
+        loops: vec![],
+    };
+    let (root, mut height) = tree.split(0, size, 0, 0);
+    let mut input = root.input(0);
+    if !tree.loops.is_empty() {
+        // one merger per loop, in a column: the first one takes the actual input,
+        // and each one takes a loop and whatever the one below it merged
+        let mergers: Vec<Structure> = (0..tree.loops.len())
+            .map(|i| tree.world.place(Merger, 0, i as Coord * Merger.height()))
+            .collect();
+        let last = tree.loops.len() - 1;
+        for (i, &merger) in mergers.iter().enumerate() {
+            if i > 0 {
+                tree.world.connect(tree.loops[i - 1], merger.input(0));
+            }
+            match mergers.get(i + 1) {
+                Some(below) => tree.world.connect(below.output(0), merger.input(1)),
+                None => tree.world.connect(tree.loops[last], merger.input(1)),
+            }
+        }
+        tree.world.connect(mergers[0].output(0), root.input(0));
+        input = mergers[0].input(0);
+        height = height.max(mergers.len() as Coord * Merger.height());
+    }
+    Blueprint {
+        size: Size {
+            w: x + 2 * (depth - 1) + 1,
+            h: height,
+        },
+        contents: tree.world,
+        inputs: vec![input],
+        outputs: tree.ends_out,
+    }
+}
+
+/// merges `n` inputs into one output out of small Mergers, which take turns between inputs.
+///
+/// the inputs are spread out so none is more than one merger deeper than another.
+/// when the output is backed up, every input gets at least half of an even share,
+/// instead of the highest inputs of a [`merger_tree`] getting nothing.
+/// that rests on the same guess about taking turns as [`balanced_splitter_tree`].
+///
+/// panics if `n` is less than 2.
+pub fn balanced_merger_tree(n: usize) -> Blueprint {
+    assert!(n >= 2, "A tree needs at least 2 ends, got {n}.");
+    let deepest = n.next_power_of_two().trailing_zeros() as Coord - 1;
+    let mut tree = BinaryTree {
+        n,
+        x: 0,
+        root_column: deepest,
+        world: World::new(),
+        ends_in: vec![],
+        ends_out: vec![],
+        loops: vec![],
+    };
+    let (root, height) = tree.merge(0, n, 0, 0);
+    Blueprint {
+        size: Size {
+            w: 2 * deepest + 1,
+            h: height,
+        },
+        contents: tree.world,
+        inputs: tree.ends_in,
+        outputs: vec![root.output(0)],
+    }
+}
+
+/// [`splitter_tree`], by how many outputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct SplitterTree;
+
+impl BlueprintGenerator for SplitterTree {
+    type Params = usize;
+
+    fn generate(&self, &n: &usize) -> Blueprint {
+        splitter_tree(n)
+    }
+}
+
+/// [`merger_tree`], by how many inputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct MergerTree;
+
+impl BlueprintGenerator for MergerTree {
+    type Params = usize;
+
+    fn generate(&self, &n: &usize) -> Blueprint {
+        merger_tree(n)
+    }
+}
+
+/// [`balanced_splitter_tree`], by how many outputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct BalancedSplitterTree;
+
+impl BlueprintGenerator for BalancedSplitterTree {
+    type Params = usize;
+
+    fn generate(&self, &n: &usize) -> Blueprint {
+        balanced_splitter_tree(n)
+    }
+}
+
+/// [`balanced_merger_tree`], by how many inputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct BalancedMergerTree;
+
+impl BlueprintGenerator for BalancedMergerTree {
+    type Params = usize;
+
+    fn generate(&self, &n: &usize) -> Blueprint {
+        balanced_merger_tree(n)
+    }
+}
@@ -1,5 +1,8 @@
+use std::str::FromStr;
+
 use Item::*;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Item {
     #[default]
     Empty = -1,
@@ -73,3 +76,18 @@ impl TryFrom<i8> for Item {
         }
     }
 }
+
+impl FromStr for Item {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "Empty" {
+            return Ok(Empty);
+        }
+        Self::ITEMS
+            .iter()
+            .copied()
+            .find(|i| format!("{i:?}") == s)
+            .ok_or(())
+    }
+}
@@ -1,4 +1,12 @@
+pub mod ascii;
+pub mod builder;
+pub mod generators;
 pub mod item;
+pub mod library;
+pub mod market;
+pub mod recipe;
+pub mod script;
+pub mod sim;
 pub mod structure;
 pub mod world;
 
@@ -0,0 +1,509 @@
+//! blueprints as text files, so modules can be shared without sharing rust code.
+//!
+//! a blueprint file is a [layout script](crate::script) with a few extra statements:
+//! ```text
+//! # gloom shard line
+//! size (8, 2)
+//! pump = AirPump @ (0, 0)
+//! ref = Refinery @ (2, 0)
+//! ref.out.0 = GloomShard
+//! pump.0 -> ref.0
+//! output shards = ref.0
+//! ```
+//! `size (w, h)` is how much room the blueprint takes up, and defaults to just enough to
+//! fit everything. `input name = a.i` and `output name = a.i` expose ports of the blueprint,
+//! numbered in the order they're written. this makes `size`, `input` and `output` bad names
+//! for structures.
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    io::{self, Write},
+    ops::Deref,
+    path::Path,
+};
+
+use crate::{
+    prelude::*,
+    script::{self, Interpreter, ParseResult},
+    world::PositionedStructureData,
+};
+
+/// file extension of blueprint files, as read by [`BlueprintLibrary::load_dir`].
+pub const EXTENSION: &str = "ifb";
+
+/// a blueprint whose ports have names.
+pub struct NamedBlueprint {
+    pub blueprint: Blueprint,
+    /// one per entry of `blueprint.inputs`.
+    pub inputs: Vec<String>,
+    /// one per entry of `blueprint.outputs`.
+    pub outputs: Vec<String>,
+    /// what the structures in `blueprint.contents` were called in the file, so
+    /// [`NamedBlueprint::write`] can call them that again. structures without one get
+    /// named after their kind and index.
+    pub names: BTreeMap<Structure, String>,
+}
+
+impl Deref for NamedBlueprint {
+    type Target = Blueprint;
+
+    fn deref(&self) -> &Self::Target {
+        &self.blueprint
+    }
+}
+
+/// names ports `in_0`, `in_1`, ... and `out_0`, `out_1`, ...
+impl From<Blueprint> for NamedBlueprint {
+    fn from(blueprint: Blueprint) -> Self {
+        Self {
+            inputs: (0..blueprint.inputs.len())
+                .map(|i| format!("in_{i}"))
+                .collect(),
+            outputs: (0..blueprint.outputs.len())
+                .map(|i| format!("out_{i}"))
+                .collect(),
+            names: BTreeMap::new(),
+            blueprint,
+        }
+    }
+}
+
+impl Placeable for &NamedBlueprint {
+    type Id = PastedBlueprint;
+
+    fn place_in(self, world: &mut World, x: Coord, y: Coord) -> Self::Id {
+        self.blueprint.place_in(world, x, y)
+    }
+}
+
+/// words that start a statement rather than name a structure.
+const KEYWORDS: &[&str] = &["size", "input", "output"];
+
+/// `AirPump` -> `air_pump`
+fn snake_case(kind: StructureKind) -> String {
+    let mut name = String::new();
+    for c in format!("{kind:?}").chars() {
+        if c.is_ascii_uppercase() && !name.is_empty() {
+            name.push('_');
+        }
+        name.push(c.to_ascii_lowercase());
+    }
+    name
+}
+
+fn check_free<P>(ports: &[(String, P)], name: &str, keyword: &str) -> ParseResult<()> {
+    if ports.iter().any(|(n, _)| n == name) {
+        Err(format!("there's already an {keyword} called {name:?}"))
+    } else {
+        Ok(())
+    }
+}
+
+impl NamedBlueprint {
+    /// which input is called `name`, for use with [`Machine::input`].
+    pub fn input_index(&self, name: &str) -> Option<usize> {
+        self.inputs.iter().position(|n| n == name)
+    }
+
+    /// which output is called `name`, for use with [`Machine::output`].
+    pub fn output_index(&self, name: &str) -> Option<usize> {
+        self.outputs.iter().position(|n| n == name)
+    }
+
+    pub fn parse(source: &str) -> io::Result<Self> {
+        Self::parse_with(Interpreter::default(), None, source)
+    }
+
+    /// carries on from wherever `interpreter` left off.
+    /// `size` is what to use if the source doesn't say.
+    pub(crate) fn parse_with(
+        mut interpreter: Interpreter,
+        mut size: Option<Size>,
+        source: &str,
+    ) -> io::Result<Self> {
+        let mut inputs = vec![];
+        let mut outputs = vec![];
+        interpreter.run(source, |interpreter, statement| {
+            let (keyword, rest) = statement.split_once(' ').unwrap_or((statement, ""));
+            match keyword {
+                "size" => {
+                    let (w, h) = script::coords(rest)?;
+                    size = Some(Size { w, h });
+                }
+                "input" | "output" => {
+                    let (name, port) = rest
+                        .split_once('=')
+                        .ok_or_else(|| format!("expected {keyword} name = a.i"))?;
+                    let name = script::ident(name.trim())?.to_owned();
+                    if keyword == "input" {
+                        check_free(&inputs, &name, keyword)?;
+                        inputs.push((name, interpreter.port_in(port)?));
+                    } else {
+                        check_free(&outputs, &name, keyword)?;
+                        outputs.push((name, interpreter.port_out(port)?));
+                    }
+                }
+                _ => interpreter.statement(statement)?,
+            }
+            Ok(())
+        })?;
+
+        let mut blueprint = Self::new(interpreter.world, size, inputs, outputs);
+        blueprint.names = interpreter
+            .names
+            .into_iter()
+            .map(|(name, structure)| (structure, name))
+            .collect();
+        Ok(blueprint)
+    }
+
+    /// `contents` with named ports. `size` defaults to just enough to fit everything,
+    /// counting from `(0, 0)`.
+    pub fn new(
+        contents: World,
+        size: Option<Size>,
+        inputs: Vec<(String, PortIn)>,
+        outputs: Vec<(String, PortOut)>,
+    ) -> Self {
+        let size = size.unwrap_or_else(|| {
+            contents
+                .bounds()
+                .map_or(Size { w: 0, h: 0 }, |(pos, size)| Size {
+                    w: pos.x + size.w,
+                    h: pos.y + size.h,
+                })
+        });
+        let (input_names, inputs) = inputs.into_iter().unzip();
+        let (output_names, outputs) = outputs.into_iter().unzip();
+        Self {
+            blueprint: Blueprint {
+                contents,
+                size,
+                inputs,
+                outputs,
+            },
+            inputs: input_names,
+            outputs: output_names,
+            names: BTreeMap::new(),
+        }
+    }
+
+    /// what each structure gets called in the file: its name if it has one that the
+    /// format can read, otherwise its kind and index, e.g. `refinery_3`.
+    fn structure_names(&self) -> Vec<String> {
+        let contents = &self.blueprint.contents;
+        let mut names: Vec<Option<String>> = contents
+            .handles()
+            .map(|s| {
+                self.names
+                    .get(&s)
+                    .filter(|name| {
+                        script::ident(name).is_ok() && !KEYWORDS.contains(&name.as_str())
+                    })
+                    .cloned()
+            })
+            .collect();
+        let mut taken: HashSet<String> = names.iter().flatten().cloned().collect();
+        for (i, name) in names.iter_mut().enumerate() {
+            if name.is_none() {
+                let mut generated = format!(
+                    "{}_{i}",
+                    snake_case(contents.structures[i].structure.kind())
+                );
+                while taken.contains(&generated) {
+                    generated.push('_');
+                }
+                taken.insert(generated.clone());
+                *name = Some(generated);
+            }
+        }
+        names.into_iter().flatten().collect()
+    }
+
+    /// writes the blueprint in the format [`NamedBlueprint::parse`] reads.
+    /// structures keep their [names](NamedBlueprint::names).
+    pub fn write(&self, f: &mut impl Write) -> io::Result<()> {
+        let Blueprint {
+            contents,
+            size,
+            inputs,
+            outputs,
+        } = &self.blueprint;
+        let names = self.structure_names();
+
+        writeln!(f, "size ({}, {})", size.w, size.h)?;
+        writeln!(f)?;
+        for (name, PositionedStructureData { pos, structure }) in
+            names.iter().zip(&contents.structures)
+        {
+            writeln!(
+                f,
+                "{name} = {:?} @ ({}, {})",
+                structure.kind(),
+                pos.x,
+                pos.y
+            )?;
+            let slots = [
+                (
+                    "in",
+                    structure.get_inputs().iter().map(|p| p.item).collect(),
+                ),
+                (
+                    "out",
+                    structure.get_outputs().iter().map(|p| p.item).collect(),
+                ),
+                ("storage", structure.get_storage().to_vec()),
+            ];
+            for (which, items) in slots {
+                for (i, item) in items.into_iter().enumerate() {
+                    if item != Empty {
+                        writeln!(f, "{name}.{which}.{i} = {item:?}")?;
+                    }
+                }
+            }
+        }
+
+        let mut wrote_connection = false;
+        for (name, s) in names.iter().zip(&contents.structures) {
+            for (i, port) in s.structure.get_outputs().iter().enumerate() {
+                if let Some(target) = port.target {
+                    if !wrote_connection {
+                        writeln!(f)?;
+                        wrote_connection = true;
+                    }
+                    let target_name = &names[target.structure_index];
+                    writeln!(f, "{name}.{i} -> {target_name}.{}", target.port)?;
+                }
+            }
+        }
+
+        if !inputs.is_empty() || !outputs.is_empty() {
+            writeln!(f)?;
+        }
+        for (name, port) in self.inputs.iter().zip(inputs) {
+            let structure = &names[port.structure_id.index];
+            writeln!(f, "input {name} = {structure}.{}", port.index)?;
+        }
+        for (name, port) in self.outputs.iter().zip(outputs) {
+            let structure = &names[port.structure_id.index];
+            writeln!(f, "output {name} = {structure}.{}", port.index)?;
+        }
+        Ok(())
+    }
+}
+
+/// a bunch of named blueprints, usually a directory of blueprint files.
+#[derive(Default)]
+pub struct BlueprintLibrary {
+    pub blueprints: BTreeMap<String, NamedBlueprint>,
+}
+
+impl BlueprintLibrary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// loads every `.ifb` file in `dir`, named after the file without its extension.
+    /// doesn't look in subdirectories.
+    pub fn load_dir(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let mut library = Self::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != EXTENSION) {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            let blueprint = NamedBlueprint::parse(&fs::read_to_string(&path)?)
+                .map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", path.display())))?;
+            library.blueprints.insert(name.to_owned(), blueprint);
+        }
+        Ok(library)
+    }
+
+    /// writes every blueprint to `dir` as `<name>.ifb`. names can have dots in them,
+    /// but not path separators, since those wouldn't load back under the same name.
+    /// nothing is written if any name is like that.
+    pub fn save_dir(&self, dir: impl AsRef<Path>) -> io::Result<()> {
+        let dir = dir.as_ref();
+        if let Some(name) = self
+            .blueprints
+            .keys()
+            .find(|name| name.is_empty() || name.contains(['/', '\\']))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{name:?} can't be used as a file name"),
+            ));
+        }
+        fs::create_dir_all(dir)?;
+        for (name, blueprint) in &self.blueprints {
+            let mut f =
+                io::BufWriter::new(fs::File::create(dir.join(format!("{name}.{EXTENSION}")))?);
+            blueprint.write(&mut f)?;
+            f.flush()?;
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&NamedBlueprint> {
+        self.blueprints.get(name)
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, blueprint: NamedBlueprint) {
+        self.blueprints.insert(name.into(), blueprint);
+    }
+}
+
+/// a [blueprint file](self), written inline as rust.
+/// ```
+/// # use immortality_factory_laboratory::blueprint;
+/// let line = blueprint! {
+///     pump = AirPump @ (0, 0);
+///     ref = Refinery @ (2, 0);
+///     ref.out.0 = GloomShard;
+///     pump.0 -> ref.0;
+///     export out ref.0 as "shards";
+/// };
+/// assert_eq!(line.output_index("shards"), Some(0));
+/// ```
+/// statements end in `;`, and are any of:
+/// - `name = Kind @ (x, y)` places a structure and names it. names only mean something
+///   inside the macro, and end up in [`NamedBlueprint::names`].
+/// - `a.i -> b.j` connects output `i` of `a` to input `j` of `b`.
+/// - `a.in.i = Item`, `a.out.i = Item`, `a.storage.i = Item` set slot items.
+/// - `export in a.i as "name"` and `export out a.i as "name"` expose ports.
+/// - `size (w, h)`, which defaults to just enough to fit everything.
+///
+/// gives a [`NamedBlueprint`]. mistakes panic, just like the calls they stand for,
+/// and so does using a name before it's placed.
+///
+/// the statements are picked apart one at a time, each one a macro call deeper than the last,
+/// so somewhere past 120 of them the compiler stops with "recursion limit reached".
+/// put `#![recursion_limit = "256"]` (or more) at the top of your crate to go further,
+/// or keep big layouts in a [blueprint file](self) instead.
+#[macro_export]
+macro_rules! blueprint {
+    ($($body:tt)*) => {{
+        let mut __world = $crate::world::World::new();
+        let mut __size = ::std::option::Option::None;
+        let mut __inputs = ::std::vec::Vec::new();
+        let mut __outputs = ::std::vec::Vec::new();
+        let mut __names = ::std::collections::BTreeMap::new();
+        $crate::__blueprint_statements!(
+            (__world, __size, __inputs, __outputs, __names)
+            $($body)*
+        );
+        $crate::library::__finish(__world, __size, __inputs, __outputs, __names)
+    }};
+}
+
+/// what a name in [`blueprint!`] stands for.
+#[doc(hidden)]
+pub fn __structure(names: &BTreeMap<&'static str, Structure>, name: &str) -> Structure {
+    *names
+        .get(name)
+        .unwrap_or_else(|| panic!("Nothing is called {name:?}."))
+}
+
+#[doc(hidden)]
+pub fn __finish(
+    contents: World,
+    size: Option<Size>,
+    inputs: Vec<(String, PortIn)>,
+    outputs: Vec<(String, PortOut)>,
+    names: BTreeMap<&'static str, Structure>,
+) -> NamedBlueprint {
+    let mut blueprint = NamedBlueprint::new(contents, size, inputs, outputs);
+    blueprint.names = names
+        .into_iter()
+        .map(|(name, structure)| (structure, name.to_owned()))
+        .collect();
+    blueprint
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __blueprint_statements {
+    ($state:tt) => {};
+    (($world:ident, $size:ident, $inputs:ident, $outputs:ident, $names:ident)
+        export in $s:ident . $i:literal as $name:literal ; $($rest:tt)*
+    ) => {
+        $inputs.push((
+            ::std::string::String::from($name),
+            $crate::world::blueprint::Machine::input(
+                &$crate::library::__structure(&$names, stringify!($s)),
+                $i,
+            ),
+        ));
+        $crate::__blueprint_statements!(($world, $size, $inputs, $outputs, $names) $($rest)*);
+    };
+    (($world:ident, $size:ident, $inputs:ident, $outputs:ident, $names:ident)
+        export out $s:ident . $i:literal as $name:literal ; $($rest:tt)*
+    ) => {
+        $outputs.push((
+            ::std::string::String::from($name),
+            $crate::world::blueprint::Machine::output(
+                &$crate::library::__structure(&$names, stringify!($s)),
+                $i,
+            ),
+        ));
+        $crate::__blueprint_statements!(($world, $size, $inputs, $outputs, $names) $($rest)*);
+    };
+    (($world:ident, $size:ident, $inputs:ident, $outputs:ident, $names:ident)
+        size ($w:expr, $h:expr) ; $($rest:tt)*
+    ) => {
+        $size = ::std::option::Option::Some($crate::world::Size { w: $w, h: $h });
+        $crate::__blueprint_statements!(($world, $size, $inputs, $outputs, $names) $($rest)*);
+    };
+    (($world:ident, $size:ident, $inputs:ident, $outputs:ident, $names:ident)
+        $name:ident = $kind:ident @ ($x:expr, $y:expr) ; $($rest:tt)*
+    ) => {
+        let __structure = $world.place($crate::structure::StructureKind::$kind, $x, $y);
+        if $names.insert(stringify!($name), __structure).is_some() {
+            panic!("{:?} is already taken.", stringify!($name));
+        }
+        $crate::__blueprint_statements!(($world, $size, $inputs, $outputs, $names) $($rest)*);
+    };
+    (($world:ident, $size:ident, $inputs:ident, $outputs:ident, $names:ident)
+        $s:ident . in . $i:literal = $item:ident ; $($rest:tt)*
+    ) => {
+        let __structure = $crate::library::__structure(&$names, stringify!($s));
+        $world.structures[__structure.index()].structure.get_inputs_mut()[$i].item =
+            $crate::item::Item::$item;
+        $crate::__blueprint_statements!(($world, $size, $inputs, $outputs, $names) $($rest)*);
+    };
+    (($world:ident, $size:ident, $inputs:ident, $outputs:ident, $names:ident)
+        $s:ident . out . $i:literal = $item:ident ; $($rest:tt)*
+    ) => {
+        let __structure = $crate::library::__structure(&$names, stringify!($s));
+        $world.structures[__structure.index()].structure.get_outputs_mut()[$i].item =
+            $crate::item::Item::$item;
+        $crate::__blueprint_statements!(($world, $size, $inputs, $outputs, $names) $($rest)*);
+    };
+    (($world:ident, $size:ident, $inputs:ident, $outputs:ident, $names:ident)
+        $s:ident . storage . $i:literal = $item:ident ; $($rest:tt)*
+    ) => {
+        let __structure = $crate::library::__structure(&$names, stringify!($s));
+        $world.structures[__structure.index()].structure.get_storage_mut()[$i] =
+            $crate::item::Item::$item;
+        $crate::__blueprint_statements!(($world, $size, $inputs, $outputs, $names) $($rest)*);
+    };
+    (($world:ident, $size:ident, $inputs:ident, $outputs:ident, $names:ident)
+        $a:ident . $i:literal -> $b:ident . $j:literal ; $($rest:tt)*
+    ) => {
+        $world.connect(
+            $crate::world::blueprint::Machine::output(
+                &$crate::library::__structure(&$names, stringify!($a)),
+                $i,
+            ),
+            $crate::world::blueprint::Machine::input(
+                &$crate::library::__structure(&$names, stringify!($b)),
+                $j,
+            ),
+        );
+        $crate::__blueprint_statements!(($world, $size, $inputs, $outputs, $names) $($rest)*);
+    };
+}
@@ -0,0 +1,207 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    ops::{Add, AddAssign},
+};
+
+use crate::{prelude::*, structure::StructureDataFull, world::PositionedStructureData};
+
+/// a pile of coins, one count per coin the Subdimensional Market spits out.
+/// the market's outputs are copper, silver and gold, in that order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Coins {
+    pub copper: u32,
+    pub silver: u32,
+    pub gold: u32,
+}
+
+impl Coins {
+    pub const fn new(copper: u32, silver: u32, gold: u32) -> Self {
+        Self {
+            copper,
+            silver,
+            gold,
+        }
+    }
+
+    pub const fn copper(copper: u32) -> Self {
+        Self::new(copper, 0, 0)
+    }
+
+    pub const fn silver(silver: u32) -> Self {
+        Self::new(0, silver, 0)
+    }
+
+    pub const fn gold(gold: u32) -> Self {
+        Self::new(0, 0, gold)
+    }
+
+    pub fn per_minute(self, rate: f64) -> Income {
+        Income {
+            copper: self.copper as f64 * rate,
+            silver: self.silver as f64 * rate,
+            gold: self.gold as f64 * rate,
+        }
+    }
+}
+
+/// coins per minute. fractional, since machines don't line up with minutes.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct Income {
+    pub copper: f64,
+    pub silver: f64,
+    pub gold: f64,
+}
+
+impl Add<Self> for Income {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            copper: self.copper + rhs.copper,
+            silver: self.silver + rhs.silver,
+            gold: self.gold + rhs.gold,
+        }
+    }
+}
+
+impl AddAssign<Self> for Income {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Display for Income {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.2} copper, {:.2} silver, {:.2} gold per minute",
+            self.copper, self.silver, self.gold
+        )
+    }
+}
+
+/// what the Subdimensional Market pays for each item, as far as you've told it.
+///
+/// the save file doesn't store prices, so there are no defaults. fill in the ones you've
+/// checked in game, and anything left out shows up as unpriced instead of as a guess.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PriceTable {
+    prices: HashMap<Item, Coins>,
+}
+
+impl PriceTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&mut self, item: Item, value: Coins) -> &mut Self {
+        self.prices.insert(item, value);
+        self
+    }
+
+    /// `None` if nobody said what it sells for.
+    pub fn get(&self, item: Item) -> Option<Coins> {
+        self.prices.get(&item).copied()
+    }
+}
+
+impl FromIterator<(Item, Coins)> for PriceTable {
+    fn from_iter<T: IntoIterator<Item = (Item, Coins)>>(iter: T) -> Self {
+        Self {
+            prices: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// what a single Subdimensional Market makes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MarketIncome {
+    pub market: Structure,
+    /// the item being sold. `Empty` if nothing says what goes in.
+    pub item: Item,
+    /// `None` if the [`PriceTable`] doesn't have it.
+    pub value: Option<Coins>,
+    pub income: Income,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MarketReport {
+    pub markets: Vec<MarketIncome>,
+    /// only counts the markets with a price.
+    pub total: Income,
+    /// how many markets sell something without a price.
+    pub unpriced: usize,
+}
+
+impl Display for MarketReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for MarketIncome {
+            market,
+            item,
+            value,
+            income,
+        } in &self.markets
+        {
+            match value {
+                Some(_) => writeln!(f, "#{} sells {item:?}: {income}", market.index)?,
+                None => writeln!(f, "#{} sells {item:?}: unpriced", market.index)?,
+            }
+        }
+        write!(f, "total: {}", self.total)?;
+        if self.unpriced > 0 {
+            write!(f, " ({} unpriced)", self.unpriced)?;
+        }
+        Ok(())
+    }
+}
+
+impl World {
+    /// what the market is fed: its own input slot, or failing that, whatever the structure
+    /// feeding it says it puts out.
+    fn market_input(&self, structure: &StructureDataFull) -> Item {
+        let [input] = structure.get_inputs() else {
+            return Empty;
+        };
+        if input.item != Empty {
+            return input.item;
+        }
+        input
+            .target
+            .and_then(|source| {
+                self.structures[source.structure_index]
+                    .structure
+                    .get_outputs()
+                    .get(source.port as usize)
+            })
+            .map_or(Empty, |port| port.item)
+    }
+
+    /// coin income of every Subdimensional Market in the world.
+    ///
+    /// assumes every market is kept busy, selling at its base
+    /// [machine speed](StructureKind::machine_speed), and that the speed is in sales per
+    /// minute, which is a guess. a market that's starved upstream will make less than this.
+    /// markets selling something `prices` doesn't have are left out of the total.
+    pub fn market_income(&self, prices: &PriceTable) -> MarketReport {
+        let rate = SubdimensionalMarket.machine_speed();
+        let mut report = MarketReport::default();
+        for (i, PositionedStructureData { structure, .. }) in self.structures.iter().enumerate() {
+            if structure.kind() != SubdimensionalMarket {
+                continue;
+            }
+            let item = self.market_input(structure);
+            let value = prices.get(item);
+            let income = value.map_or_else(Income::default, |coins| coins.per_minute(rate));
+            report.total += income;
+            report.unpriced += usize::from(value.is_none());
+            report.markets.push(MarketIncome {
+                market: self.structure_handle(i),
+                item,
+                value,
+                income,
+            });
+        }
+        report
+    }
+}
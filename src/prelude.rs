@@ -6,7 +6,8 @@ pub use crate::{
         StructureKind::{self, *},
     },
     world::{
-        HasSize, Offset, Placeable, PortIn, PortOut, Size, Structure, World,
-        blueprint::{Blueprint, Entity, Machine, PastedBlueprint, PastedWorld},
+        Collision, HasSize, Offset, Placeable, PortIn, PortOut, Position, Save, Size, Structure,
+        World, WorldIds,
+        blueprint::{Blueprint, Chain, Entity, Machine, Mirrored, PastedBlueprint, PastedWorld},
     },
 };
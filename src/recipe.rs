@@ -0,0 +1,256 @@
+//! what goes into and comes out of each machine, so slots can show it.
+//!
+//! the game fills in a machine's slots once it's picked a recipe. generated saves start out
+//! with every slot `Empty` instead, which looks broken until things start flowing.
+//! [`World::configure`] and [`World::propagate_items`] fill them in up front.
+//!
+//! NOTE: only recipes that don't depend on the game's recipe list are built in:
+//! the air pump, the market's coins, and machines that pass things through unchanged.
+//! everything else goes in a [`RecipeBook`] by hand until someone writes the list down.
+
+use std::collections::BTreeMap;
+
+use crate::{prelude::*, structure::StructureDataFull, world::PositionedStructureData};
+
+/// the items in each of a machine's input and output slots while it's making something.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Recipe {
+    pub kind: StructureKind,
+    /// one per input slot.
+    pub inputs: Vec<Item>,
+    /// one per output slot.
+    pub outputs: Vec<Item>,
+}
+
+/// the kinds that put out whatever they're given.
+const PASS_THROUGH: &[StructureKind] = &[Splitter, BigSplitter, Merger, BigMerger, StorageVault];
+
+impl Recipe {
+    /// panics if `kind` doesn't have that many input or output slots.
+    pub fn new(kind: StructureKind, inputs: &[Item], outputs: &[Item]) -> Self {
+        let slots = StructureDataFull::from(kind);
+        assert_eq!(
+            inputs.len(),
+            slots.get_inputs().len(),
+            "{kind:?} has {} inputs, got {inputs:?}",
+            slots.get_inputs().len()
+        );
+        assert_eq!(
+            outputs.len(),
+            slots.get_outputs().len(),
+            "{kind:?} has {} outputs, got {outputs:?}",
+            slots.get_outputs().len()
+        );
+        Self {
+            kind,
+            inputs: inputs.to_vec(),
+            outputs: outputs.to_vec(),
+        }
+    }
+
+    /// `item` in every slot, for the kinds that don't change what goes through them
+    /// (splitters, mergers, and storage vaults). `None` for anything else.
+    pub fn pass_through(kind: StructureKind, item: Item) -> Option<Self> {
+        if !PASS_THROUGH.contains(&kind) {
+            return None;
+        }
+        let slots = StructureDataFull::from(kind);
+        Some(Self {
+            kind,
+            inputs: vec![item; slots.get_inputs().len()],
+            outputs: vec![item; slots.get_outputs().len()],
+        })
+    }
+
+    pub fn air_pump() -> Self {
+        Self::new(AirPump, &[], &[RichAir])
+    }
+
+    /// selling `item` for the three kinds of coin.
+    pub fn market(item: Item) -> Self {
+        Self::new(
+            SubdimensionalMarket,
+            &[item],
+            &[CopperCoin, SilverCoin, GoldCoin],
+        )
+    }
+
+    /// fills in `structure`'s slots. panics if it's the wrong kind.
+    pub fn apply(&self, structure: &mut StructureDataFull) {
+        assert_eq!(
+            structure.kind(),
+            self.kind,
+            "Tried to use a {:?} recipe on a {:?}.",
+            self.kind,
+            structure.kind()
+        );
+        for (port, &item) in structure.get_inputs_mut().iter_mut().zip(&self.inputs) {
+            port.item = item;
+        }
+        for (port, &item) in structure.get_outputs_mut().iter_mut().zip(&self.outputs) {
+            port.item = item;
+        }
+    }
+}
+
+/// recipes to pick from, by machine and what it makes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RecipeBook {
+    recipes: BTreeMap<(StructureKind, Item), Recipe>,
+}
+
+/// just the [built in](self) ones.
+impl Default for RecipeBook {
+    fn default() -> Self {
+        let mut book = Self {
+            recipes: BTreeMap::new(),
+        };
+        book.insert(Recipe::air_pump());
+        book
+    }
+}
+
+impl RecipeBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// files `recipe` under its first output, replacing whatever was there.
+    /// recipes without outputs are filed under their first input.
+    pub fn insert(&mut self, recipe: Recipe) {
+        let key = recipe
+            .outputs
+            .first()
+            .or(recipe.inputs.first())
+            .copied()
+            .unwrap_or(Empty);
+        self.recipes.insert((recipe.kind, key), recipe);
+    }
+
+    /// how `kind` makes `item`. pass-through kinds and the market don't need to be added.
+    pub fn get(&self, kind: StructureKind, item: Item) -> Option<Recipe> {
+        if let Some(recipe) = self.recipes.get(&(kind, item)) {
+            return Some(recipe.clone());
+        }
+        match kind {
+            SubdimensionalMarket => Some(Recipe::market(item)),
+            _ => Recipe::pass_through(kind, item),
+        }
+    }
+
+    /// the one recipe `kind` has, if there's only one to pick from.
+    pub fn only(&self, kind: StructureKind) -> Option<&Recipe> {
+        let mut recipes = self.recipes.values().filter(|r| r.kind == kind);
+        let recipe = recipes.next()?;
+        recipes.next().is_none().then_some(recipe)
+    }
+}
+
+/// a connection whose ends say different things are going through it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Mismatch {
+    pub output: PortOut,
+    pub input: PortIn,
+    pub sent: Item,
+    pub expected: Item,
+}
+
+impl World {
+    /// fills in `structure`'s slots from `recipe`.
+    pub fn configure(&mut self, structure: Structure, recipe: &Recipe) {
+        self.get_structure(structure);
+        recipe.apply(&mut self.structures[structure.index()].structure);
+    }
+
+    /// fills in `Empty` slots by following connections: an input gets whatever feeds it,
+    /// pass-through machines (splitters, mergers, vaults) put out what they take in,
+    /// and markets put out coins.
+    /// machines with only one recipe in `book` get that recipe's outputs.
+    /// slots that already say something are left alone.
+    ///
+    /// returns how many slots got filled in.
+    pub fn propagate_items(&mut self, book: &RecipeBook) -> usize {
+        let mut filled = 0;
+        for PositionedStructureData { structure, .. } in &mut self.structures {
+            if let Some(recipe) = book.only(structure.kind()) {
+                for (port, &item) in structure.get_outputs_mut().iter_mut().zip(&recipe.outputs) {
+                    if port.item == Empty && item != Empty {
+                        port.item = item;
+                        filled += 1;
+                    }
+                }
+            }
+        }
+        // every pass fills in at least one more slot, or it's done
+        loop {
+            let before = filled;
+            for index in 0..self.structures.len() {
+                for port in 0..self.structures[index].structure.get_inputs().len() {
+                    let input = self.structures[index].structure.get_inputs()[port];
+                    let Some(source) = input.target else {
+                        continue;
+                    };
+                    let sent = self.structures[source.structure_index]
+                        .structure
+                        .get_outputs()[source.port as usize]
+                        .item;
+                    if input.item == Empty && sent != Empty {
+                        self.structures[index].structure.get_inputs_mut()[port].item = sent;
+                        filled += 1;
+                    }
+                }
+                let structure = &mut self.structures[index].structure;
+                let kind = structure.kind();
+                if !(PASS_THROUGH.contains(&kind) || kind == SubdimensionalMarket) {
+                    continue;
+                }
+                let Some(recipe) = structure
+                    .get_inputs()
+                    .iter()
+                    .map(|port| port.item)
+                    .find(|&item| item != Empty)
+                    .and_then(|item| book.get(kind, item))
+                else {
+                    continue;
+                };
+                for (port, &item) in structure.get_outputs_mut().iter_mut().zip(&recipe.outputs) {
+                    if port.item == Empty {
+                        port.item = item;
+                        filled += 1;
+                    }
+                }
+            }
+            if filled == before {
+                return filled;
+            }
+        }
+    }
+
+    /// connections where the output says it's sending something other than what the input
+    /// says it wants. `Empty` on either end doesn't count, since that's just not configured.
+    pub fn slot_mismatches(&self) -> Vec<Mismatch> {
+        let mut mismatches = vec![];
+        for (index, s) in self.structures.iter().enumerate() {
+            for (port, data) in s.structure.get_outputs().iter().enumerate() {
+                let Some(target) = data.target else {
+                    continue;
+                };
+                let expected = self.structures[target.structure_index]
+                    .structure
+                    .get_inputs()[target.port as usize]
+                    .item;
+                if data.item != Empty && expected != Empty && data.item != expected {
+                    let output = self.structure_handle(index).output(port);
+                    mismatches.push(Mismatch {
+                        output,
+                        input: self.output_target(output).unwrap(),
+                        sent: data.item,
+                        expected,
+                    });
+                }
+            }
+        }
+        mismatches
+    }
+}
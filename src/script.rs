@@ -0,0 +1,213 @@
+//! layout scripts, so a factory can be written down without touching rust.
+//!
+//! one statement per line, `#` starts a comment:
+//! ```text
+//! # a pump feeding a refinery
+//! pump = AirPump @ (0, 0)
+//! ref = Refinery @ (2, 0)
+//! pump.0 -> ref.0
+//! ```
+//! `name = Kind @ (x, y)` places a structure and names it,
+//! `a.i -> b.j` connects output `i` of `a` to input `j` of `b`,
+//! and `a.in.i = Item`, `a.out.i = Item` and `a.storage.i = Item` fill in slots.
+
+use std::{collections::HashMap, io};
+
+use crate::{
+    prelude::*,
+    structure::StructureDataFull,
+    world::{Position, PositionedStructureData},
+};
+
+pub(crate) type ParseResult<T> = Result<T, String>;
+
+pub(crate) fn ident(name: &str) -> ParseResult<&str> {
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if valid {
+        Ok(name)
+    } else {
+        Err(format!("{name:?} is not a valid name"))
+    }
+}
+
+pub(crate) fn number<T: std::str::FromStr>(n: &str) -> ParseResult<T> {
+    n.trim()
+        .parse()
+        .map_err(|_| format!("expected a number, got {n:?}"))
+}
+
+pub(crate) fn kind(name: &str) -> ParseResult<StructureKind> {
+    name.parse()
+        .map_err(|()| format!("no structure is called {name:?}"))
+}
+
+pub(crate) fn item(name: &str) -> ParseResult<Item> {
+    name.parse()
+        .map_err(|()| format!("no item is called {name:?}"))
+}
+
+/// `(x, y)`
+pub(crate) fn coords(coords: &str) -> ParseResult<(Coord, Coord)> {
+    let inner = coords
+        .trim()
+        .strip_prefix('(')
+        .and_then(|c| c.strip_suffix(')'))
+        .ok_or_else(|| format!("expected (x, y), got {coords:?}"))?;
+    let (x, y) = inner
+        .split_once(',')
+        .ok_or_else(|| format!("expected (x, y), got {coords:?}"))?;
+    Ok((number(x)?, number(y)?))
+}
+
+/// reads a script line by line, keeping track of what each name refers to.
+#[derive(Debug, Default)]
+pub(crate) struct Interpreter {
+    pub(crate) world: World,
+    pub(crate) names: HashMap<String, Structure>,
+}
+
+impl Interpreter {
+    pub(crate) fn structure(&self, name: &str) -> ParseResult<Structure> {
+        self.names
+            .get(name)
+            .copied()
+            .ok_or_else(|| format!("nothing is called {name:?}"))
+    }
+
+    /// `name.i`
+    fn port(&self, port: &str) -> ParseResult<(Structure, usize)> {
+        let (name, index) = port
+            .trim()
+            .split_once('.')
+            .ok_or_else(|| format!("expected name.port, got {port:?}"))?;
+        Ok((self.structure(name)?, number(index)?))
+    }
+
+    pub(crate) fn port_out(&self, port: &str) -> ParseResult<PortOut> {
+        let (structure, index) = self.port(port)?;
+        let connector = structure.kind.connectors().outputs.get(index).copied();
+        if !connector.is_some_and(|c| c.port.non_null()) {
+            return Err(format!("{:?} has no output #{index}", structure.kind));
+        }
+        Ok(structure.output(index))
+    }
+
+    pub(crate) fn port_in(&self, port: &str) -> ParseResult<PortIn> {
+        let (structure, index) = self.port(port)?;
+        let connector = structure.kind.connectors().inputs.get(index).copied();
+        if !connector.is_some_and(|c| c.port.non_null()) {
+            return Err(format!("{:?} has no input #{index}", structure.kind));
+        }
+        Ok(structure.input(index))
+    }
+
+    fn place(&mut self, name: &str, rhs: &str) -> ParseResult<()> {
+        let name = ident(name.trim())?;
+        if self.names.contains_key(name) {
+            return Err(format!("{name:?} is already taken"));
+        }
+        let (kind_name, at) = rhs
+            .split_once('@')
+            .ok_or_else(|| format!("expected Kind @ (x, y), got {rhs:?}"))?;
+        let kind = kind(kind_name.trim())?;
+        let (x, y) = coords(at)?;
+        let structure = StructureDataFull::from(kind);
+        let placed = PositionedStructureData {
+            pos: Position { x, y },
+            structure,
+        };
+        if let Some(other) = self.world.find_structure_collision(&placed) {
+            return Err(format!(
+                "{name} would overlap a {:?} at ({}, {})",
+                other.structure.kind(),
+                other.pos.x,
+                other.pos.y
+            ));
+        }
+        let structure = self.world.place(structure, x, y);
+        self.names.insert(name.to_owned(), structure);
+        Ok(())
+    }
+
+    fn connect(&mut self, from: &str, to: &str) -> ParseResult<()> {
+        let from = self.port_out(from)?;
+        let to = self.port_in(to)?;
+        if self.world.output_target(from).is_some() || self.world.input_source(to).is_some() {
+            return Err("port is already connected".to_owned());
+        }
+        self.world.connect(from, to);
+        Ok(())
+    }
+
+    /// `name.in.i = Item`, `name.out.i = Item` or `name.storage.i = Item`
+    fn set_slot(&mut self, slot: &str, item_name: &str) -> ParseResult<()> {
+        let mut parts = slot.trim().split('.');
+        let (Some(name), Some(which), Some(index), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(format!("expected name.in/out/storage.index, got {slot:?}"));
+        };
+        let structure = self.structure(name)?;
+        let index: usize = number(index)?;
+        let item = item(item_name.trim())?;
+        let data = &mut self.world.structures[structure.index].structure;
+        let slot = match which {
+            "in" => data
+                .get_inputs_mut()
+                .get_mut(index)
+                .map(|port| &mut port.item),
+            "out" => data
+                .get_outputs_mut()
+                .get_mut(index)
+                .map(|port| &mut port.item),
+            "storage" => data.get_storage_mut().get_mut(index),
+            _ => return Err(format!("expected in, out or storage, got {which:?}")),
+        };
+        *slot.ok_or_else(|| format!("{:?} has no {which} slot #{index}", structure.kind))? = item;
+        Ok(())
+    }
+
+    /// runs a single statement. comments and blank lines are already gone by now.
+    pub(crate) fn statement(&mut self, statement: &str) -> ParseResult<()> {
+        if let Some((from, to)) = statement.split_once("->") {
+            self.connect(from, to)
+        } else if let Some((lhs, rhs)) = statement.split_once('=') {
+            if lhs.contains('.') {
+                self.set_slot(lhs, rhs)
+            } else {
+                self.place(lhs, rhs)
+            }
+        } else {
+            Err(format!("don't know what to do with {statement:?}"))
+        }
+    }
+
+    /// runs every line of a script through `statement`.
+    pub(crate) fn run(
+        &mut self,
+        source: &str,
+        mut statement: impl FnMut(&mut Self, &str) -> ParseResult<()>,
+    ) -> io::Result<()> {
+        for (i, line) in source.lines().enumerate() {
+            let line = line.split_once('#').map_or(line, |(code, _)| code).trim();
+            if line.is_empty() {
+                continue;
+            }
+            statement(self, line).map_err(|e| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {e}", i + 1))
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// builds the world a script describes.
+pub fn parse(source: &str) -> io::Result<World> {
+    let mut interpreter = Interpreter::default();
+    interpreter.run(source, Interpreter::statement)?;
+    Ok(interpreter.world)
+}
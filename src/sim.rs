@@ -0,0 +1,359 @@
+//! a rough simulation of items flowing through a world, to see what's holding things up.
+//!
+//! NOTE: the game's recipes aren't modelled, so items are just counted, not told apart.
+//! every craft takes one item from each connected input and puts one in each connected output.
+//! machines with nothing on their inputs at all (air pumps) make things out of thin air,
+//! and outputs that go nowhere (like the market's coins) just disappear.
+//! crafts take as long as [`StructureKind::machine_speed`] says, taken as crafts per minute,
+//! which is a guess.
+//!
+//! splitters, mergers and storage move items instantly. Big Splitters and Big Mergers go
+//! lowest port first. the small ones are assumed to take turns, which nothing in the game
+//! data backs up. storage vaults hold up to [`VAULT_CAPACITY`] items, single storage holds one
+//! and never lets go.
+//!
+//! [`TICK`], [`SLOT_CAPACITY`] and [`VAULT_CAPACITY`] are made up too, picked to be about
+//! right rather than read out of the game. so trust where things pile up and what's waiting
+//! on what, more than the exact numbers.
+
+use std::fmt::{self, Display};
+
+use crate::prelude::*;
+
+/// how long one step of the simulation is, in seconds.
+/// the game's own tick isn't known, this is just short next to any craft.
+pub const TICK: f64 = 0.1;
+
+/// how many items fit in each input and output slot. an assumption, the save doesn't say.
+pub const SLOT_CAPACITY: u32 = 2;
+
+/// how many items a storage vault holds. also an assumption, nothing in the save says.
+pub const VAULT_CAPACITY: u32 = 16;
+
+/// what a structure spent a tick doing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum State {
+    /// crafting, or moving items along.
+    Working,
+    /// done, but there's no room to put the result.
+    Blocked,
+    /// waiting for something to come in.
+    Starved,
+    /// not hooked up to anything that could give it work.
+    Idle,
+}
+
+#[derive(Debug, Clone)]
+struct MachineState {
+    kind: StructureKind,
+    /// [`StructureKind::machine_speed`], looked up once instead of every tick.
+    speed: f64,
+    /// the ports that have a connection, which doesn't change while running.
+    connected_inputs: Vec<usize>,
+    connected_outputs: Vec<usize>,
+    inputs: Vec<u32>,
+    outputs: Vec<u32>,
+    /// `Some` while crafting, from 0 to 1.
+    progress: Option<f64>,
+    /// items sitting inside splitters, mergers and storage.
+    held: u32,
+    /// which port takes the next turn, for the small splitter and merger.
+    next_in: usize,
+    next_out: usize,
+    ticks: [u64; 4],
+    crafts: u64,
+}
+
+/// a world's machines, running.
+#[derive(Debug, Clone)]
+pub struct Simulation<'a> {
+    world: &'a World,
+    machines: Vec<MachineState>,
+    ticks: u64,
+}
+
+impl<'a> Simulation<'a> {
+    pub fn new(world: &'a World) -> Self {
+        fn connected<T>(targets: impl Iterator<Item = Option<T>>) -> Vec<usize> {
+            targets
+                .enumerate()
+                .filter(|(_, target)| target.is_some())
+                .map(|(port, _)| port)
+                .collect()
+        }
+        let machines = world
+            .structures
+            .iter()
+            .map(|s| MachineState {
+                kind: s.structure.kind(),
+                speed: s.structure.kind().machine_speed(),
+                connected_inputs: connected(s.structure.get_inputs().iter().map(|p| p.target)),
+                connected_outputs: connected(s.structure.get_outputs().iter().map(|p| p.target)),
+                inputs: vec![0; s.structure.get_inputs().len()],
+                outputs: vec![0; s.structure.get_outputs().len()],
+                progress: None,
+                held: 0,
+                next_in: 0,
+                next_out: 0,
+                ticks: [0; 4],
+                crafts: 0,
+            })
+            .collect();
+        Self {
+            world,
+            machines,
+            ticks: 0,
+        }
+    }
+
+    /// one tick of a machine that crafts things.
+    fn craft(&mut self, index: usize) -> State {
+        let machine = &mut self.machines[index];
+        let Some(progress) = machine.progress else {
+            if !machine.inputs.is_empty() && machine.connected_inputs.is_empty() {
+                return State::Idle;
+            }
+            if machine
+                .connected_inputs
+                .iter()
+                .any(|&port| machine.inputs[port] == 0)
+            {
+                return State::Starved;
+            }
+            for &port in &machine.connected_inputs {
+                machine.inputs[port] -= 1;
+            }
+            machine.progress = Some(0.0);
+            return State::Working;
+        };
+        if progress < 1.0 {
+            machine.progress = Some(progress + TICK * machine.speed / 60.0);
+            return State::Working;
+        }
+        if machine
+            .connected_outputs
+            .iter()
+            .any(|&port| machine.outputs[port] >= SLOT_CAPACITY)
+        {
+            return State::Blocked;
+        }
+        for &port in &machine.connected_outputs {
+            machine.outputs[port] += 1;
+        }
+        machine.progress = None;
+        machine.crafts += 1;
+        State::Working
+    }
+
+    /// one tick of a splitter, merger or storage.
+    fn route(&mut self, index: usize) -> State {
+        let capacity = match self.machines[index].kind {
+            StorageVault => VAULT_CAPACITY,
+            _ => 1,
+        };
+        let in_order = |machine: &MachineState, ports: usize| -> Vec<usize> {
+            match machine.kind {
+                Splitter | Merger => (0..ports).map(|i| (machine.next_in + i) % ports).collect(),
+                _ => (0..ports).collect(),
+            }
+        };
+        let machine = &mut self.machines[index];
+        let outputs = std::mem::take(&mut machine.connected_outputs);
+        let any_input = !machine.connected_inputs.is_empty();
+        let mut moved = false;
+        loop {
+            let mut moved_now = false;
+            if machine.held < capacity {
+                let ports = machine.inputs.len();
+                if let Some(port) = in_order(machine, ports)
+                    .into_iter()
+                    .find(|&port| machine.inputs[port] > 0)
+                {
+                    machine.inputs[port] -= 1;
+                    machine.held += 1;
+                    machine.next_in = (port + 1) % ports;
+                    moved_now = true;
+                }
+            }
+            if machine.held > 0 && !outputs.is_empty() {
+                let start = match machine.kind {
+                    Splitter | Merger => machine.next_out,
+                    _ => 0,
+                };
+                if let Some(&port) = (0..outputs.len())
+                    .map(|i| &outputs[(start + i) % outputs.len()])
+                    .find(|&&port| machine.outputs[port] < SLOT_CAPACITY)
+                {
+                    machine.outputs[port] += 1;
+                    machine.held -= 1;
+                    machine.next_out =
+                        (outputs.iter().position(|&p| p == port).unwrap() + 1) % outputs.len();
+                    moved_now = true;
+                }
+            }
+            if !moved_now {
+                break;
+            }
+            moved = true;
+        }
+        machine.connected_outputs = outputs;
+        if moved {
+            State::Working
+        } else if machine.held > 0 {
+            State::Blocked
+        } else if any_input {
+            State::Starved
+        } else {
+            State::Idle
+        }
+    }
+
+    /// moves items along every connection, as many as fit.
+    fn transfer(&mut self) {
+        for (index, s) in self.world.structures.iter().enumerate() {
+            for (port, data) in s.structure.get_outputs().iter().enumerate() {
+                let Some(target) = data.target else {
+                    continue;
+                };
+                let (to, to_port) = (target.structure_index, target.port as usize);
+                let room = SLOT_CAPACITY - self.machines[to].inputs[to_port];
+                let amount = self.machines[index].outputs[port].min(room);
+                self.machines[index].outputs[port] -= amount;
+                self.machines[to].inputs[to_port] += amount;
+            }
+        }
+    }
+
+    pub fn step(&mut self) {
+        self.transfer();
+        for index in 0..self.machines.len() {
+            let state = match self.machines[index].speed > 0.0 {
+                true => self.craft(index),
+                false => self.route(index),
+            };
+            self.machines[index].ticks[state as usize] += 1;
+        }
+        self.ticks += 1;
+    }
+
+    /// runs for about `seconds` of game time.
+    pub fn run(&mut self, seconds: f64) {
+        for _ in 0..(seconds / TICK).round() as u64 {
+            self.step();
+        }
+    }
+
+    /// forgets what happened so far, but leaves the items where they are.
+    /// handy to skip over everything starting up.
+    pub fn reset_counters(&mut self) {
+        for machine in &mut self.machines {
+            machine.ticks = [0; 4];
+            machine.crafts = 0;
+        }
+        self.ticks = 0;
+    }
+
+    /// what everything has been up to since the start, or since [`Simulation::reset_counters`].
+    pub fn report(&self) -> FlowReport {
+        let total = self.ticks.max(1) as f64;
+        let machines: Vec<MachineFlow> = self
+            .machines
+            .iter()
+            .enumerate()
+            .map(|(index, machine)| {
+                let fraction = |state: State| machine.ticks[state as usize] as f64 / total;
+                MachineFlow {
+                    structure: self.world.structure_handle(index),
+                    working: fraction(State::Working),
+                    blocked: fraction(State::Blocked),
+                    starved: fraction(State::Starved),
+                    idle: fraction(State::Idle),
+                    crafts: machine.crafts,
+                }
+            })
+            .collect();
+        // whatever's busiest without being held up by what comes after it
+        // is what everything else is waiting on
+        let bottleneck = machines
+            .iter()
+            .zip(&self.machines)
+            .filter(|(_, machine)| machine.speed > 0.0)
+            .map(|(m, _)| m)
+            .map(|m| (m.working - m.blocked, m.structure))
+            .filter(|&(score, _)| score > 0.0)
+            .max_by(|a, b| a.0.total_cmp(&b.0).then(b.1.cmp(&a.1)))
+            .map(|(_, structure)| structure);
+        FlowReport {
+            seconds: self.ticks as f64 * TICK,
+            machines,
+            bottleneck,
+        }
+    }
+}
+
+/// how one structure spent its time, as fractions that add up to 1.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct MachineFlow {
+    pub structure: Structure,
+    pub working: f64,
+    pub blocked: f64,
+    pub starved: f64,
+    pub idle: f64,
+    /// finished crafts. always 0 for splitters, mergers and storage.
+    pub crafts: u64,
+}
+
+/// see [`Simulation::report`].
+///
+/// NOTE: craft times come from [`StructureKind::machine_speed`] read as crafts per minute,
+/// which is a guess (see the module docs). if that's off, `seconds` and the craft counts
+/// are off by the same factor, but the percentages and the bottleneck roughly hold, since
+/// every machine is scaled alike.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowReport {
+    pub seconds: f64,
+    /// one per structure, in order.
+    pub machines: Vec<MachineFlow>,
+    /// the machine that's busy all the time without being blocked, which everything
+    /// upstream is backed up behind and everything downstream is waiting on.
+    /// `None` if nothing's doing anything.
+    pub bottleneck: Option<Structure>,
+}
+
+impl Display for FlowReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let percent = |fraction: f64| (fraction * 100.0).round();
+        writeln!(f, "over {:.0} seconds:", self.seconds)?;
+        for m in &self.machines {
+            write!(
+                f,
+                "#{} {:?}: {}% working, {}% blocked, {}% starved, {}% idle",
+                m.structure.index(),
+                m.structure.kind(),
+                percent(m.working),
+                percent(m.blocked),
+                percent(m.starved),
+                percent(m.idle)
+            )?;
+            if m.crafts > 0 {
+                write!(f, ", {} crafts", m.crafts)?;
+            }
+            writeln!(f)?;
+        }
+        match self.bottleneck {
+            Some(s) => write!(f, "bottleneck: #{} {:?}", s.index(), s.kind()),
+            None => write!(f, "bottleneck: nothing's running"),
+        }
+    }
+}
+
+impl World {
+    /// simulates `seconds` of things starting up, then `seconds` more to see how it runs.
+    pub fn flow_report(&self, seconds: f64) -> FlowReport {
+        let mut simulation = Simulation::new(self);
+        simulation.run(seconds);
+        simulation.reset_counters();
+        simulation.run(seconds);
+        simulation.report()
+    }
+}
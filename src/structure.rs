@@ -1,8 +1,8 @@
 use crate::prelude::*;
 use crate::world::Position;
 
-use std::fmt::{self, Display};
 use std::io::{self, Write};
+use std::str::FromStr;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ConnectorData {
@@ -17,6 +17,7 @@ pub struct IoData {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) struct PortOutRaw {
     pub(crate) structure_index: usize,
     pub(crate) port: u8,
@@ -32,6 +33,7 @@ impl From<PortOut> for PortOutRaw {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) struct PortInRaw {
     pub(crate) structure_index: usize,
     pub(crate) port: u8,
@@ -47,18 +49,21 @@ impl From<PortIn> for PortInRaw {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PortOutData {
     pub item: Item,
     pub(crate) target: Option<PortInRaw>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PortInData {
     pub item: Item,
     pub(crate) target: Option<PortOutRaw>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum StructureData {
     AirPump {
         output: Item,
@@ -192,6 +197,7 @@ impl From<StructureData> for StructureDataFull {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum StructureDataFull {
     AirPump {
         outputs: [PortOutData; 1],
@@ -262,6 +268,14 @@ impl StructureDataFull {
         }
     }
 
+    pub fn get_storage_mut(&mut self) -> &mut [Item] {
+        match self {
+            Self::Refinery { storage, .. } => storage,
+            Self::StorageVault { storage, .. } => storage,
+            _ => &mut [],
+        }
+    }
+
     pub fn get_inputs(&self) -> &[PortInData] {
         use StructureDataFull::*;
         match self {
@@ -396,7 +410,7 @@ impl StructureDataFull {
                 r#"{{+index+:{i}.0,+column+:{px}.0,+row+:{py}.0,+content_column+:{sx}.0,+type+:1,+content_row+:{sy}.0,+content+:{item_id}.0,+connected_machine+:{target_id},+connected_machine_slot_index+:{target_port}.0,+connected_machine_x+:{target_x}.0,+connected_machine_y+:{target_y}.0}}"#
             )?;
         }
-        write!(f, "],{},+input_list+:[", self.machine_type())?;
+        write!(f, "],{},+input_list+:[", self.kind().machine_type())?;
         let mut comma = false;
         for (
             i,
@@ -440,74 +454,151 @@ impl StructureDataFull {
         }
         Ok(())
     }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StructureKind {
+    AirPump,
+    Refinery,
+    Disharmonizer,
+    Unifier,
+    SubdimensionalMarket,
+    Splitter,
+    Merger,
+    StorageVault,
+    AbysalDoor, // [sic]
+    SingleStorage,
+    Laboratory,
+    RitualInfuser,
+    BigMerger,
+    BigSplitter,
+}
+
+impl StructureKind {
+    pub const KINDS: &[StructureKind] = &[
+        Self::AirPump,
+        Self::Refinery,
+        Self::Disharmonizer,
+        Self::Unifier,
+        Self::SubdimensionalMarket,
+        Self::Splitter,
+        Self::Merger,
+        Self::StorageVault,
+        Self::AbysalDoor,
+        Self::SingleStorage,
+        Self::Laboratory,
+        Self::RitualInfuser,
+        Self::BigMerger,
+        Self::BigSplitter,
+    ];
 
     fn machine_type(&self) -> &'static str {
-        match *self {
-            Self::AirPump { .. } => {
+        match self {
+            Self::AirPump => {
                 "+type+:0,+machine_type+:{+name+:+Air Pump+,+type+:0,+description+:+Sucks in potent air from the surrounding valley and puts it in a bottle.+,+sprite+:5,+machine_cost+:{+cost_type_list+:[8,0,0,1,1,2,2,5,15,16,16,16,7,7,7,7,7,7,20,20,20,21,21,21,21,21,21],+cost_amount_list+:[3.0,2.0,4.0,4.0,4.0,4.0,3.0,4.0,5.0,3.0,3.0,3.0,4.0,4.0,3.0,3.0,2.0,2.0,3.0,3.0,2.0,3.0,3.0,2.0,2.0,1.0,1.0]},+cost_input+:0.0,+speed_increase+:8.0,+unlocked+:true,+machine_speed+:8.0}"
             }
-            Self::Refinery { .. } => {
+            Self::Refinery => {
                 "+type+:1,+machine_type+:{+name+:+Refinery+,+type+:1,+description+:+Improves a resource, turning it into something better.+,+sprite+:35,+machine_cost+:{+cost_type_list+:[0,1,1,1,1,3,3,3,3,3,3,3,15,15,15,15,16,16,16],+cost_amount_list+:[3.0,2.0,2.0,2.0,2.0,4.0,3.0,3.0,2.0,2.0,2.0,2.0,3.0,3.0,2.0,2.0,2.0,2.0,1.0]},+cost_input+:0.0,+speed_increase+:8.0,+unlocked+:true,+machine_speed+:16.}"
             }
-            Self::Disharmonizer { .. } => {
+            Self::Disharmonizer => {
                 "+type+:2,+machine_type+:{+name+:+Disharmonizer+,+type+:2,+description+:+Breaks resources apart by nature and magical sequence.+,+sprite+:37,+machine_cost+:{+cost_type_list+:[1,5,5,15,17,17,17,17,17,17,17,17,18,18,18,18,18,18,20,20,20,20,20,21,21,21,21],+cost_amount_list+:[3.0,4.0,2.0,4.0,3.0,3.0,2.0,2.0,2.0,2.0,2.0,2.0,3.0,3.0,2.0,2.0,2.0,2.0,3.0,3.0,3.0,2.0,2.0,4.0,3.0,2.0,1.0]},+cost_input+:0.0,+speed_increase+:8.0,+unlocked+:true,+machine_speed+:16.0}"
             }
-            Self::Unifier { .. } => {
+            Self::Unifier => {
                 "+type+:3,+machine_type+:{+name+:+Unifier+,+type+:3,+description+:+Converges multiple resources into one.+,+sprite+:61,+machine_cost+:{+cost_type_list+:[2,15,15,15,15,15,7,7,7,16,16,16,16],+cost_amount_list+:[4.0,4.0,3.0,2.0,2.0,2.0,3.0,3.0,2.0,4.0,3.0,2.0,1.0]},+cost_input+:0.0,+speed_increase+:8.0,+unlocked+:true,+machine_speed+:16.0}"
             }
-            Self::SubdimensionalMarket { .. } => {
+            Self::SubdimensionalMarket => {
                 "+type+:4,+machine_type+:{+name+:+Subdimensional Market+,+type+:4.0,+description+:+Sell any resource for coin. Some are more worth than others.+,+sprite+:52,+machine_cost+:{+cost_type_list+:[10.0,11.0,11.0,8.0,8.0,21.0],+cost_amount_list+:[4.0,4.0,3.0,4.0,3.0,2.0]},+cost_input+:0.0,+speed_increase+:4.0,+unlocked+:true,+machine_speed+:8.0}"
             }
-            Self::Splitter { .. } => {
+            Self::Splitter => {
                 "+type+:5,+machine_type+:{+name+:+Splitter+,+type+:5.0,+description+:+Split an incomming connection into two outputs.+,+sprite+:24,+machine_cost+:{+cost_type_list+:[1.0,1.0,5.0,5.0,5.0,5.0,5.0],+cost_amount_list+:[3.0,3.0,3.0,3.0,2.0,2.0,1.0]},+cost_input+:0.0,+speed_increase+:1.0,+unlocked+:true,+machine_speed+:-1.0}"
             }
-            Self::Merger { .. } => {
+            Self::Merger => {
                 "+type+:6,+machine_type+:{+name+:+Merger+,+type+:6.0,+description+:+Merges two incomming connections into one output.+,+sprite+:25,+machine_cost+:{+cost_type_list+:[1.0,1.0,1.0,1.0,4.0,4.0,5.0,5.0,5.0,5.0,5.0],+cost_amount_list+:[3.0,3.0,2.0,2.0,3.0,2.0,3.0,3.0,2.0,2.0,1.0]},+cost_input+:0.0,+speed_increase+:1.0,+unlocked+:true,+machine_speed+:-1.0}"
             }
-            Self::StorageVault { .. } => {
+            Self::StorageVault => {
                 "+type+:7,+machine_type+:{+name+:+Storage Vault+,+type+:7.0,+description+:+A machine which keeps your resources safe behind thick glass.+,+sprite+:6,+machine_cost+:{+cost_type_list+:[4.0,5.0,5.0,5.0,5.0,5.0],+cost_amount_list+:[3.0,3.0,3.0,2.0,2.0,1.0]},+cost_input+:0.0,+speed_increase+:1.0,+unlocked+:true,+machine_speed+:-1.0}"
             }
-            Self::AbysalDoor { .. } => {
+            Self::AbysalDoor => {
                 "+type+:8,+machine_type+:{+name+:+Abysal Door+,+type+:8.0,+description+:+Get rid of all you don't have a need for.+,+sprite+:3,+machine_cost+:{+cost_type_list+:[2.0,2.0,2.0,2.0,2.0],+cost_amount_list+:[4.0,3.0,3.0,2.0,1.0]},+cost_input+:0.0,+speed_increase+:4.0,+unlocked+:true,+machine_speed+:2.0}"
             }
-            Self::SingleStorage { .. } => {
+            Self::SingleStorage => {
                 "+type+:9,+machine_type+:{+name+:+Single Storage+,+type+:9.0,+description+:+A single storage place for a single resource.+,+sprite+:17,+machine_cost+:{+cost_type_list+:[0.0,0.0,0.0,0.0,0.0,0.0,0.0,0.0,0.0,1.0,5.0,5.0,5.0],+cost_amount_list+:[2.0,2.0,2.0,2.0,2.0,2.0,2.0,2.0,1.0,1.0,2.0,2.0,1.0]},+cost_input+:0.0,+speed_increase+:1.0,+unlocked+:true,+machine_speed+:-1.0}"
             }
-            Self::Laboratory { .. } => {
+            Self::Laboratory => {
                 "+type+:10,+machine_type+:{+name+:+Laboratory+,+type+:10.0,+description+:+Used to research more stuff.+,+sprite+:45,+machine_cost+:{+cost_type_list+:[8],+cost_amount_list+:[100.0]},+cost_input+:10.0,+speed_increase+:32.0,+unlocked+:false,+machine_speed+:4.0}"
             }
-            Self::RitualInfuser { .. } => {
+            Self::RitualInfuser => {
                 "+type+:11,+machine_type+:{+name+:+Ritual Infuser+,+type+:11.0,+description+:+Automate magical rituals. Used to create the phylactery.+,+sprite+:44,+machine_cost+:{+cost_type_list+:[21.0,25.0,25.0,25.0,25.0],+cost_amount_list+:[8.0,2.0,2.0,1.0,1.0]},+cost_input+:0.0,+speed_increase+:1.0,+unlocked+:true,+machine_speed+:1.0}"
             }
-            Self::BigMerger { .. } => {
+            Self::BigMerger => {
                 "+type+:12,+machine_type+:{+name+:+Big Merger+,+type+:12,+description+:+Merges Inputs. Lowest always first.+,+sprite+:53,+machine_cost+:{+cost_type_list+:[5,5,5,5,5,5,5],+cost_amount_list+:[3.0,3.0,3.0,2.0,2.0,2.0,1.0]},+cost_input+:0.0,+speed_increase+:1.0,+unlocked+:true,+machine_speed+:-10}"
             }
-            Self::BigSplitter { .. } => {
+            Self::BigSplitter => {
                 "+type+:13,+machine_type+:{+name+:+Big Splitter+,+type+:13,+description+:+Splits Outputs. Lowest always first.+,+sprite+:22,+machine_cost+:{+cost_type_list+:[5,5,5,5,5,5,5],+cost_amount_list+:[3.0,3.0,3.0,2.0,2.0,2.0,1.0]},+cost_input+:0.0,+speed_increase+:1.0,+unlocked+:true,+machine_speed+:-10}"
             }
         }
     }
-}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub enum StructureKind {
-    AirPump,
-    Refinery,
-    Disharmonizer,
-    Unifier,
-    SubdimensionalMarket,
-    Splitter,
-    Merger,
-    StorageVault,
-    AbysalDoor, // [sic]
-    SingleStorage,
-    Laboratory,
-    RitualInfuser,
-    BigMerger,
-    BigSplitter,
-}
+    /// pulls a single `+key+:value` field out of the machine type blob.
+    fn machine_type_field(&self, key: &str) -> &'static str {
+        let blob = self.machine_type();
+        let key = format!("+{key}+:");
+        let start = blob
+            .find(&key)
+            .unwrap_or_else(|| panic!("{self:?} machine type has no {key} field."))
+            + key.len();
+        let value = &blob[start..];
+        let len = if value.starts_with('[') {
+            value.find(']').expect("lists are always closed") + 1
+        } else {
+            value
+                .find([',', '}'])
+                .expect("machine type blob is always closed")
+        };
+        &value[..len]
+    }
+
+    /// base speed before any research upgrades.
+    /// the save doesn't say what unit this is in, so we assume crafts per minute.
+    /// negative for splitters and mergers, which move items instantly.
+    pub fn machine_speed(&self) -> f64 {
+        self.machine_type_field("machine_speed")
+            .parse()
+            .expect("machine_speed is always a number")
+    }
+
+    /// a single character to draw this structure with in ascii renders.
+    pub fn symbol(&self) -> char {
+        match self {
+            Self::AirPump => 'P',
+            Self::Refinery => 'R',
+            Self::Disharmonizer => 'D',
+            Self::Unifier => 'U',
+            Self::SubdimensionalMarket => 'C',
+            Self::Splitter => 's',
+            Self::Merger => 'm',
+            Self::StorageVault => 'V',
+            Self::AbysalDoor => 'X',
+            Self::SingleStorage => 'O',
+            Self::Laboratory => 'L',
+            Self::RitualInfuser => 'I',
+            Self::BigMerger => 'M',
+            Self::BigSplitter => 'S',
+        }
+    }
+
+    pub fn from_symbol(symbol: char) -> Option<Self> {
+        Self::KINDS.iter().copied().find(|k| k.symbol() == symbol)
+    }
+
+    pub(crate) fn from_object_number(number: u8) -> Option<Self> {
+        Self::KINDS
+            .iter()
+            .copied()
+            .find(|k| k.object_number() == number)
+    }
 
-impl StructureKind {
     /// i have no idea what this number means
     fn object_number(&self) -> u8 {
         match self {
@@ -754,6 +845,18 @@ impl StructureKind {
     }
 }
 
+impl FromStr for StructureKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::KINDS
+            .iter()
+            .copied()
+            .find(|k| format!("{k:?}") == s)
+            .ok_or(())
+    }
+}
+
 impl From<&StructureDataFull> for StructureKind {
     fn from(value: &StructureDataFull) -> Self {
         use StructureDataFull::*;
@@ -117,31 +117,73 @@ impl PastedWorld {
 //     }
 // }
 
-impl Placeable for &World {
-    type Id = PastedWorld;
-
-    fn place_in(self, world: &mut World, x: Coord, y: Coord) -> PastedWorld {
+impl World {
+    /// pastes every structure in, wherever `position` says it goes.
+    fn paste_into(
+        &self,
+        world: &mut World,
+        offset: Offset,
+        position: impl Fn(&PositionedStructureData) -> Position,
+    ) -> PastedWorld {
         let base_index = world.structures.len();
-        let offset = Offset { x, y };
         let pasted_world = PastedWorld {
             blueprint_id: self.world_id,
             host_id: world.world_id,
             base_index,
             offset,
         };
-        for &structure in &self.structures {
-            let structure = PositionedStructureData {
-                pos: structure.pos + offset,
+        let structures: Vec<_> = self
+            .structures
+            .iter()
+            .map(|structure| PositionedStructureData {
+                pos: position(structure),
                 structure: structure.structure._map_inside(&pasted_world),
-            };
-            world.assert_no_structure_collision(&structure);
-            world.structures.push(structure);
+            })
+            .collect();
+        // all of them first, so a collision doesn't leave half a blueprint behind
+        for structure in &structures {
+            world.assert_no_structure_collision(structure);
         }
+        world.structures.extend(structures);
         pasted_world
     }
 }
 
+impl Placeable for &World {
+    type Id = PastedWorld;
+
+    fn place_in(self, world: &mut World, x: Coord, y: Coord) -> PastedWorld {
+        let offset = Offset { x, y };
+        self.paste_into(world, offset, |s| s.pos + offset)
+    }
+}
+
 impl World {
+    /// checks that `blueprint` could be pasted at `(x, y)` without landing on anything.
+    pub fn check_paste(&self, blueprint: &World, x: Coord, y: Coord) -> Result<(), Collision> {
+        let offset = Offset { x, y };
+        self.check_positions(blueprint, |s| s.pos + offset)
+    }
+
+    /// checks that `blueprint` could go in with each structure at `position(structure)`,
+    /// the same way [`World::paste_into`] puts them.
+    fn check_positions(
+        &self,
+        blueprint: &World,
+        position: impl Fn(&PositionedStructureData) -> Position,
+    ) -> Result<(), Collision> {
+        for structure in &blueprint.structures {
+            let new = PositionedStructureData {
+                pos: position(structure),
+                structure: structure.structure,
+            };
+            if let Some(old) = self.find_structure_collision(&new) {
+                return Err(Collision::new(&new, old));
+            }
+        }
+        Ok(())
+    }
+
     /// for hardcore users
     pub fn stack_iter(
         &mut self,
@@ -171,8 +213,106 @@ impl World {
     ) -> Vec<PastedWorld> {
         self.stack_iter(blueprint, x, y, dx, dy, count).collect()
     }
+
+    /// pastes `rows` rows of `columns` copies each, starting at `(x, y)`.
+    /// copies in a row are `dx` apart, rows are `dy` apart.
+    ///
+    /// `grid[row][column]` is the copy in that spot.
+    #[allow(clippy::too_many_arguments)]
+    pub fn grid(
+        &mut self,
+        blueprint: &Self,
+        x: Coord,
+        y: Coord,
+        dx: Coord,
+        dy: Coord,
+        columns: usize,
+        rows: usize,
+    ) -> Vec<Vec<PastedWorld>> {
+        (0..rows)
+            .map(|row| {
+                let y = y + dy * row as Coord;
+                self.stack(blueprint, x, y, dx, 0, columns)
+            })
+            .collect()
+    }
+
+    /// connects each copy to the next one.
+    /// for each `(output, input)` in `links`, both in the pasted blueprint,
+    /// `output` of `copies[i]` goes to `input` of `copies[i + 1]`.
+    pub fn chain(&mut self, copies: &[PastedWorld], links: &[(PortOut, PortIn)]) {
+        for pair in copies.windows(2) {
+            for &(output, input) in links {
+                self.connect(pair[0].get(output), pair[1].get(input));
+            }
+        }
+    }
+
+    /// [`World::stack`]s `count` copies `(dx, dy)` apart, then [`World::chain`]s them
+    /// with `links`.
+    ///
+    /// input `k` of the result is the input of `links[k]` on the first copy,
+    /// and output `k` is the output of `links[k]` on the last copy,
+    /// so the whole chain can be hooked up like one machine.
+    #[allow(clippy::too_many_arguments)]
+    pub fn stack_chained(
+        &mut self,
+        blueprint: &Self,
+        x: Coord,
+        y: Coord,
+        dx: Coord,
+        dy: Coord,
+        count: usize,
+        links: &[(PortOut, PortIn)],
+    ) -> Chain {
+        assert!(count > 0, "Cannot chain zero copies.");
+        let copies = self.stack(blueprint, x, y, dx, dy, count);
+        self.chain(&copies, links);
+        Chain {
+            copies,
+            links: links.to_vec(),
+        }
+    }
+
+    /// [`World::chain`]s every row of a [`World::grid`], left to right.
+    pub fn chain_rows(&mut self, grid: &[Vec<PastedWorld>], links: &[(PortOut, PortIn)]) {
+        for row in grid {
+            self.chain(row, links);
+        }
+    }
+}
+
+/// copies of a blueprint connected one after the other, see [`World::stack_chained`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Chain {
+    pub copies: Vec<PastedWorld>,
+    links: Vec<(PortOut, PortIn)>,
+}
+
+impl Chain {
+    pub fn first(&self) -> &PastedWorld {
+        &self.copies[0]
+    }
+
+    pub fn last(&self) -> &PastedWorld {
+        &self.copies[self.copies.len() - 1]
+    }
 }
 
+impl Machine for Chain {
+    fn input(&self, port: usize) -> PortIn {
+        self.first().get(self.links[port].1)
+    }
+
+    fn output(&self, port: usize) -> PortOut {
+        self.last().get(self.links[port].0)
+    }
+}
+
+/// with the `serde` feature, `inputs` and `outputs` come back pointing at the
+/// deserialized `contents`, whatever id it ended up with.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "BlueprintData"))]
 pub struct Blueprint {
     pub contents: World,
     pub size: Size,
@@ -180,17 +320,62 @@ pub struct Blueprint {
     pub outputs: Vec<PortOut>,
 }
 
+/// a [`Blueprint`] fresh off the disk, with ports that still have the old world's id.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct BlueprintData {
+    contents: World,
+    size: Size,
+    inputs: Vec<PortIn>,
+    outputs: Vec<PortOut>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<BlueprintData> for Blueprint {
+    type Error = String;
+
+    fn try_from(data: BlueprintData) -> Result<Self, Self::Error> {
+        let BlueprintData {
+            contents,
+            size,
+            mut inputs,
+            mut outputs,
+        } = data;
+        let ports = inputs
+            .iter_mut()
+            .map(|p| &mut p.structure_id)
+            .chain(outputs.iter_mut().map(|p| &mut p.structure_id));
+        for structure in ports {
+            let Some(s) = contents.structures.get(structure.index) else {
+                return Err(format!("port on missing structure #{}", structure.index));
+            };
+            if s.structure.kind() != structure.kind {
+                return Err(format!(
+                    "port on #{} says it's a {:?}, but it's a {:?}",
+                    structure.index,
+                    structure.kind,
+                    s.structure.kind()
+                ));
+            }
+            structure.world_id = contents.world_id;
+        }
+        Ok(Self {
+            contents,
+            size,
+            inputs,
+            outputs,
+        })
+    }
+}
+
 impl HasSize for Blueprint {
     fn size(&self) -> Size {
         self.size
     }
 }
 
-impl Placeable for &Blueprint {
-    type Id = PastedBlueprint;
-
-    fn place_in(self, world: &mut World, x: Coord, y: Coord) -> Self::Id {
-        let world = self.contents.place_in(world, x, y);
+impl Blueprint {
+    fn ports_inside(&self, world: PastedWorld) -> PastedBlueprint {
         PastedBlueprint {
             world,
             size: self.size,
@@ -210,6 +395,15 @@ impl Placeable for &Blueprint {
     }
 }
 
+impl Placeable for &Blueprint {
+    type Id = PastedBlueprint;
+
+    fn place_in(self, world: &mut World, x: Coord, y: Coord) -> Self::Id {
+        let world = self.contents.place_in(world, x, y);
+        self.ports_inside(world)
+    }
+}
+
 pub struct PastedBlueprint {
     world: PastedWorld,
     size: Size,
@@ -217,6 +411,12 @@ pub struct PastedBlueprint {
     outputs: Vec<PortOut>,
 }
 
+impl PastedBlueprint {
+    pub fn get<E: Entity>(&self, entity: E) -> E {
+        self.world.get(entity)
+    }
+}
+
 impl HasSize for PastedBlueprint {
     fn size(&self) -> Size {
         self.size
@@ -257,3 +457,149 @@ impl Machine for PastedBlueprint {
 //         }
 //     }
 // }
+
+/// something to be pasted flipped left to right and/or top to bottom.
+///
+/// each structure's position is reflected within the bounding box, the smallest box around
+/// every structure for worlds and `(0, 0)` to `size` for blueprints.
+/// the structures themselves don't turn around, so their ports stay on the same sides.
+/// connections are kept as they are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Mirrored<T> {
+    pub inner: T,
+    pub flip_x: bool,
+    pub flip_y: bool,
+}
+
+impl<T> Mirrored<T> {
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            flip_x: false,
+            flip_y: false,
+        }
+    }
+
+    /// flips left to right. doing it twice puts it back.
+    pub fn mirrored_x(self) -> Self {
+        Self {
+            flip_x: !self.flip_x,
+            ..self
+        }
+    }
+
+    /// flips top to bottom. doing it twice puts it back.
+    pub fn mirrored_y(self) -> Self {
+        Self {
+            flip_y: !self.flip_y,
+            ..self
+        }
+    }
+
+    /// where `structure` ends up after flipping within `bounds`.
+    fn reflect(
+        &self,
+        (origin, size): (Position, Size),
+        structure: &PositionedStructureData,
+    ) -> Position {
+        let mut pos = structure.pos;
+        if self.flip_x {
+            pos.x = origin.x * 2 + size.w - (structure.pos.x + structure.structure.width());
+        }
+        if self.flip_y {
+            pos.y = origin.y * 2 + size.h - (structure.pos.y + structure.structure.height());
+        }
+        pos
+    }
+}
+
+impl World {
+    pub fn mirrored_x(&self) -> Mirrored<&Self> {
+        Mirrored::new(self).mirrored_x()
+    }
+
+    pub fn mirrored_y(&self) -> Mirrored<&Self> {
+        Mirrored::new(self).mirrored_y()
+    }
+}
+
+impl Blueprint {
+    pub fn mirrored_x(&self) -> Mirrored<&Self> {
+        Mirrored::new(self).mirrored_x()
+    }
+
+    pub fn mirrored_y(&self) -> Mirrored<&Self> {
+        Mirrored::new(self).mirrored_y()
+    }
+}
+
+impl Mirrored<&World> {
+    /// where each structure goes, pasted at `offset`.
+    fn position(&self, offset: Offset) -> impl Fn(&PositionedStructureData) -> Position + '_ {
+        // no bounds means no structures, so nothing to flip
+        let bounds = self.inner.bounds();
+        move |s| bounds.map_or(s.pos, |bounds| self.reflect(bounds, s)) + offset
+    }
+}
+
+impl Mirrored<&Blueprint> {
+    fn position(&self, offset: Offset) -> impl Fn(&PositionedStructureData) -> Position + '_ {
+        let bounds = (Position { x: 0, y: 0 }, self.inner.size);
+        move |s| self.reflect(bounds, s) + offset
+    }
+}
+
+impl Placeable for Mirrored<&World> {
+    type Id = PastedWorld;
+
+    fn place_in(self, world: &mut World, x: Coord, y: Coord) -> Self::Id {
+        let offset = Offset { x, y };
+        self.inner.paste_into(world, offset, self.position(offset))
+    }
+}
+
+impl HasSize for Mirrored<&Blueprint> {
+    fn size(&self) -> Size {
+        self.inner.size
+    }
+}
+
+impl Placeable for Mirrored<&Blueprint> {
+    type Id = PastedBlueprint;
+
+    fn place_in(self, world: &mut World, x: Coord, y: Coord) -> Self::Id {
+        let offset = Offset { x, y };
+        let pasted = self
+            .inner
+            .contents
+            .paste_into(world, offset, self.position(offset));
+        self.inner.ports_inside(pasted)
+    }
+}
+
+impl World {
+    /// [`World::paste`], flipped. nothing is pasted if it would land on something.
+    pub fn paste_mirrored(
+        &mut self,
+        blueprint: Mirrored<&World>,
+        x: Coord,
+        y: Coord,
+    ) -> Result<PastedWorld, Collision> {
+        self.check_positions(blueprint.inner, blueprint.position(Offset { x, y }))?;
+        Ok(self.place(blueprint, x, y))
+    }
+
+    /// [`World::paste_blueprint`], flipped. nothing is pasted if it would land on something.
+    pub fn paste_blueprint_mirrored(
+        &mut self,
+        blueprint: Mirrored<&Blueprint>,
+        x: Coord,
+        y: Coord,
+    ) -> Result<PastedBlueprint, Collision> {
+        self.check_positions(
+            &blueprint.inner.contents,
+            blueprint.position(Offset { x, y }),
+        )?;
+        Ok(self.place(blueprint, x, y))
+    }
+}
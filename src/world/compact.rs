@@ -0,0 +1,144 @@
+use std::collections::HashSet;
+
+use super::*;
+
+/// how many times [`World::compact`] goes over everything looking for short wires.
+const WIRE_PASSES: usize = 16;
+
+impl World {
+    /// whether structure `index` could sit at `pos` with at least `gap` tiles
+    /// between it and everything else.
+    fn fits(&self, index: usize, pos: Position, gap: Coord) -> bool {
+        let size = self.structures[index].structure.size();
+        self.structures.iter().enumerate().all(|(i, other)| {
+            let o = other.pos;
+            let s = other.structure.size();
+            i == index
+                || pos.x >= o.x + s.w + gap
+                || o.x >= pos.x + size.w + gap
+                || pos.y >= o.y + s.h + gap
+                || o.y >= pos.y + size.h + gap
+        })
+    }
+
+    /// total length of the wires touching structure `index`, if it were at `pos`.
+    fn wire_length_at(&self, index: usize, pos: Position) -> f64 {
+        let structure = self.structure_handle(index);
+        let at = |i: usize| match i == index {
+            true => pos,
+            false => self.structures[i].pos,
+        };
+        let length = |a: Position, b: Position| f64::from(a.x - b.x).hypot(f64::from(a.y - b.y));
+        self.downstream(structure)
+            .chain(self.upstream(structure))
+            .map(|other| length(pos, at(other.index)))
+            .sum()
+    }
+
+    /// slides everything as far left (or up, if `vertical`) as it goes without getting
+    /// closer than `gap` to anything, and not past `limit`. returns whether anything moved.
+    fn slide(&mut self, pinned: &HashSet<usize>, gap: Coord, limit: Coord, vertical: bool) -> bool {
+        // (start, length) along the way things slide, then the same across it
+        let axes = |pos: Position, size: Size| match vertical {
+            false => (pos.x, size.w, pos.y, size.h),
+            true => (pos.y, size.h, pos.x, size.w),
+        };
+        let mut order: Vec<usize> = (0..self.structures.len())
+            .filter(|i| !pinned.contains(i))
+            .collect();
+        order.sort_by_key(|&i| {
+            let pos = self.structures[i].pos;
+            if vertical { pos.y } else { pos.x }
+        });
+        let mut moved = false;
+        for index in order {
+            let s = &self.structures[index];
+            let (start, _, across, across_len) = axes(s.pos, s.structure.size());
+            let mut target = limit;
+            for (i, other) in self.structures.iter().enumerate() {
+                let (o_start, o_len, o_across, o_across_len) =
+                    axes(other.pos, other.structure.size());
+                let in_the_way =
+                    across < o_across + o_across_len + gap && o_across < across + across_len + gap;
+                if i != index && in_the_way && o_start < start {
+                    target = target.max(o_start + o_len + gap);
+                }
+            }
+            if target < start {
+                let pos = &mut self.structures[index].pos;
+                match vertical {
+                    false => pos.x = target,
+                    true => pos.y = target,
+                }
+                moved = true;
+            }
+        }
+        moved
+    }
+
+    /// moves structures closer together, to take up less room and shorten wires.
+    /// connections stay as they are, and so do indices, so handles keep working.
+    ///
+    /// `pinned` structures don't move, and nothing gets closer than `gap` tiles to anything
+    /// else. everything stays inside the current [bounds](World::bounds), pushed towards its
+    /// top left corner, then gets nudged around to shorten its wires where there's room.
+    ///
+    /// it's greedy, so it won't find the best possible layout, just a tighter one.
+    /// panics if a pinned structure isn't from this world, or if `gap` is negative.
+    pub fn compact(&mut self, pinned: &[Structure], gap: Coord) {
+        assert!(gap >= 0, "Cannot keep a negative gap, got {gap}.");
+        let Some((origin, _)) = self.bounds() else {
+            return;
+        };
+        let pinned: HashSet<usize> = pinned
+            .iter()
+            .map(|&s| self.get_structure_index(s))
+            .collect();
+        let squeeze = |world: &mut Self| {
+            let mut moved = true;
+            while moved {
+                moved = world.slide(&pinned, gap, origin.x, false);
+                moved |= world.slide(&pinned, gap, origin.y, true);
+            }
+        };
+        squeeze(self);
+
+        let (_, size) = self.bounds().unwrap();
+        for _ in 0..WIRE_PASSES {
+            let mut improved = false;
+            for index in 0..self.structures.len() {
+                if pinned.contains(&index) {
+                    continue;
+                }
+                let pos = self.structures[index].pos;
+                let s = self.structures[index].structure.size();
+                let mut best = (self.wire_length_at(index, pos), pos);
+                for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                    let new = Position {
+                        x: pos.x + dx,
+                        y: pos.y + dy,
+                    };
+                    let inside = new.x >= origin.x
+                        && new.y >= origin.y
+                        && new.x + s.w <= origin.x + size.w
+                        && new.y + s.h <= origin.y + size.h;
+                    if !inside || !self.fits(index, new, gap) {
+                        continue;
+                    }
+                    let length = self.wire_length_at(index, new);
+                    if length < best.0 {
+                        best = (length, new);
+                    }
+                }
+                if best.1 != pos {
+                    self.structures[index].pos = best.1;
+                    improved = true;
+                }
+            }
+            if !improved {
+                break;
+            }
+        }
+        squeeze(self);
+    }
+}
@@ -0,0 +1,254 @@
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+use super::*;
+
+/// which list of items a [`SlotChange`] is in, named like in [layout scripts](crate::script).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Slot {
+    Input,
+    Output,
+    Storage,
+}
+
+impl Display for Slot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Input => "in",
+            Self::Output => "out",
+            Self::Storage => "storage",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Moved {
+    /// the structure in the old world.
+    pub old: Structure,
+    /// the same structure in the new world.
+    pub new: Structure,
+    pub from: Position,
+    pub to: Position,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SlotChange {
+    /// in the new world.
+    pub structure: Structure,
+    pub slot: Slot,
+    pub index: usize,
+    pub before: Item,
+    pub after: Item,
+}
+
+/// what it takes to get from one world to another, see [`World::diff`].
+///
+/// structures are the same if they're the same kind in the same spot. whatever's left over
+/// is paired up by kind, in order, and counts as moved. anything still left over
+/// was added or removed.
+///
+/// connections and items are only compared between structures in both worlds, since
+/// everything on an added or removed structure obviously changed along with it.
+/// they use handles into the new world.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct WorldDiff {
+    /// in the new world.
+    pub added: Vec<(Structure, Position)>,
+    /// in the old world.
+    pub removed: Vec<(Structure, Position)>,
+    pub moved: Vec<Moved>,
+    pub connected: Vec<(PortOut, PortIn)>,
+    pub disconnected: Vec<(PortOut, PortIn)>,
+    pub slots: Vec<SlotChange>,
+}
+
+impl WorldDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.moved.is_empty()
+            && self.connected.is_empty()
+            && self.disconnected.is_empty()
+            && self.slots.is_empty()
+    }
+}
+
+/// `- #3 AirPump at (0, 0)`, with `+` for added, `-` for removed, `~` for moved,
+/// and `*` for changed items. indices are in the new world, except for removed structures.
+impl Display for WorldDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no changes");
+        }
+        for (s, pos) in &self.removed {
+            writeln!(f, "- #{} {:?} at ({}, {})", s.index, s.kind, pos.x, pos.y)?;
+        }
+        for (s, pos) in &self.added {
+            writeln!(f, "+ #{} {:?} at ({}, {})", s.index, s.kind, pos.x, pos.y)?;
+        }
+        for Moved { old, new, from, to } in &self.moved {
+            write!(f, "~ #{} {:?} moved", new.index, new.kind)?;
+            if old.index != new.index {
+                write!(f, " (was #{})", old.index)?;
+            }
+            writeln!(f, " from ({}, {}) to ({}, {})", from.x, from.y, to.x, to.y)?;
+        }
+        for (sign, connections) in [("-", &self.disconnected), ("+", &self.connected)] {
+            for (output, input) in connections {
+                writeln!(
+                    f,
+                    "{sign} #{} out {} -> #{} in {}",
+                    output.structure_id.index, output.index, input.structure_id.index, input.index
+                )?;
+            }
+        }
+        for change in &self.slots {
+            writeln!(
+                f,
+                "* #{} {:?} {} {}: {:?} -> {:?}",
+                change.structure.index,
+                change.structure.kind,
+                change.slot,
+                change.index,
+                change.before,
+                change.after
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl World {
+    /// every connection as `(from, output, to, input)`, with structure indices
+    /// put through `index`. connections touching something `index` skips are left out.
+    fn connections_by(
+        &self,
+        index: impl Fn(usize) -> Option<usize>,
+    ) -> BTreeSet<(usize, u8, usize, u8)> {
+        let mut connections = BTreeSet::new();
+        for (from, s) in self.structures.iter().enumerate() {
+            for (output, port) in s.structure.get_outputs().iter().enumerate() {
+                let Some(target) = port.target else {
+                    continue;
+                };
+                if let (Some(from), Some(to)) = (index(from), index(target.structure_index)) {
+                    connections.insert((from, output as u8, to, target.port));
+                }
+            }
+        }
+        connections
+    }
+
+    /// what changed going from `self` to `new`.
+    pub fn diff(&self, new: &World) -> WorldDiff {
+        let mut diff = WorldDiff::default();
+        // new index of each old structure
+        let mut matched = vec![None; self.structures.len()];
+        let mut matched_new = vec![false; new.structures.len()];
+
+        let mut by_spot = HashMap::<_, VecDeque<usize>>::new();
+        for (i, s) in self.structures.iter().enumerate() {
+            by_spot
+                .entry((s.structure.kind(), s.pos))
+                .or_default()
+                .push_back(i);
+        }
+        for (j, s) in new.structures.iter().enumerate() {
+            if let Some(i) = by_spot
+                .get_mut(&(s.structure.kind(), s.pos))
+                .and_then(VecDeque::pop_front)
+            {
+                matched[i] = Some(j);
+                matched_new[j] = true;
+            }
+        }
+
+        let mut by_kind = HashMap::<_, VecDeque<usize>>::new();
+        for (i, s) in self.structures.iter().enumerate() {
+            if matched[i].is_none() {
+                by_kind.entry(s.structure.kind()).or_default().push_back(i);
+            }
+        }
+        for (j, s) in new.structures.iter().enumerate() {
+            if matched_new[j] {
+                continue;
+            }
+            match by_kind
+                .get_mut(&s.structure.kind())
+                .and_then(VecDeque::pop_front)
+            {
+                Some(i) => {
+                    matched[i] = Some(j);
+                    matched_new[j] = true;
+                    diff.moved.push(Moved {
+                        old: self.structure_handle(i),
+                        new: new.structure_handle(j),
+                        from: self.structures[i].pos,
+                        to: s.pos,
+                    });
+                }
+                None => diff.added.push((new.structure_handle(j), s.pos)),
+            }
+        }
+        for (i, s) in self.structures.iter().enumerate() {
+            if matched[i].is_none() {
+                diff.removed.push((self.structure_handle(i), s.pos));
+            }
+        }
+
+        let before = self.connections_by(|i| matched[i]);
+        let after = new.connections_by(|j| matched_new[j].then_some(j));
+        let port_pair = |&(from, output, to, input): &(usize, u8, usize, u8)| {
+            (
+                PortOut {
+                    structure_id: new.structure_handle(from),
+                    index: output,
+                },
+                PortIn {
+                    structure_id: new.structure_handle(to),
+                    index: input,
+                },
+            )
+        };
+        diff.disconnected = before.difference(&after).map(port_pair).collect();
+        diff.connected = after.difference(&before).map(port_pair).collect();
+
+        for (i, old) in self.structures.iter().enumerate() {
+            let Some(j) = matched[i] else {
+                continue;
+            };
+            let old = &old.structure;
+            let new_structure = &new.structures[j].structure;
+            let slots = [
+                (
+                    Slot::Input,
+                    old.get_inputs().iter().map(|p| p.item).collect::<Vec<_>>(),
+                    new_structure.get_inputs().iter().map(|p| p.item).collect(),
+                ),
+                (
+                    Slot::Output,
+                    old.get_outputs().iter().map(|p| p.item).collect(),
+                    new_structure.get_outputs().iter().map(|p| p.item).collect(),
+                ),
+                (
+                    Slot::Storage,
+                    old.get_storage().to_vec(),
+                    new_structure.get_storage().to_vec(),
+                ),
+            ];
+            for (slot, before, after) in slots {
+                for (index, (before, after)) in before.into_iter().zip(after).enumerate() {
+                    if before != after {
+                        diff.slots.push(SlotChange {
+                            structure: new.structure_handle(j),
+                            slot,
+                            index,
+                            before,
+                            after,
+                        });
+                    }
+                }
+            }
+        }
+        diff
+    }
+}
@@ -0,0 +1,107 @@
+use super::*;
+use crate::generators::{merger_tree, splitter_tree};
+
+impl World {
+    /// the closest spot to `near` where `blueprint` fits without landing on anything,
+    /// going around in growing squares. there's always room eventually.
+    fn find_room(&self, blueprint: &World, near: Position) -> Position {
+        let fits = |x, y| self.check_paste(blueprint, x, y).is_ok();
+        if fits(near.x, near.y) {
+            return near;
+        }
+        for distance in 1.. {
+            for d in -distance..=distance {
+                for (x, y) in [
+                    (near.x + d, near.y - distance),
+                    (near.x + d, near.y + distance),
+                    (near.x - distance, near.y + d),
+                    (near.x + distance, near.y + d),
+                ] {
+                    if fits(x, y) {
+                        return Position { x, y };
+                    }
+                }
+            }
+        }
+        unreachable!()
+    }
+
+    /// connects `output` to every one of `inputs`, through a [`splitter_tree`] placed
+    /// somewhere free next to the output's structure. `None` if there's only one input,
+    /// which gets connected directly.
+    ///
+    /// the splitters fill the lowest output first, so earlier inputs get served first.
+    /// panics on no inputs, the same input twice, or ports that are already connected.
+    pub fn connect_many(&mut self, output: PortOut, inputs: &[PortIn]) -> Option<PastedBlueprint> {
+        assert!(!inputs.is_empty(), "Tried to connect an output to nothing.");
+        // before placing anything, so there's no tree left dangling
+        assert!(
+            inputs
+                .iter()
+                .enumerate()
+                .all(|(i, input)| !inputs[..i].contains(input)),
+            "Tried to connect the same input twice."
+        );
+        assert!(
+            self.output_target(output).is_none()
+                && inputs.iter().all(|&i| self.input_source(i).is_none()),
+            "Structure is already connected."
+        );
+        if let [input] = inputs {
+            self.connect(output, *input);
+            return None;
+        }
+        let tree = splitter_tree(inputs.len());
+        let source = self.get_structure(output.structure_id);
+        let near = Position {
+            x: source.pos.x + source.structure.width() + 1,
+            y: source.pos.y,
+        };
+        let pos = self.find_room(&tree.contents, near);
+        let tree = self.place(&tree, pos.x, pos.y);
+        self.connect(output, tree.input(0));
+        for (i, &input) in inputs.iter().enumerate() {
+            self.connect(tree.output(i), input);
+        }
+        Some(tree)
+    }
+
+    /// connects every one of `outputs` to `input`, through a [`merger_tree`] placed
+    /// somewhere free next to the input's structure. `None` if there's only one output,
+    /// which gets connected directly.
+    ///
+    /// the mergers take from the lowest input first, so earlier outputs get through first.
+    /// panics on no outputs, the same output twice, or ports that are already connected.
+    pub fn merge_into(&mut self, outputs: &[PortOut], input: PortIn) -> Option<PastedBlueprint> {
+        assert!(!outputs.is_empty(), "Tried to connect nothing to an input.");
+        assert!(
+            outputs
+                .iter()
+                .enumerate()
+                .all(|(i, output)| !outputs[..i].contains(output)),
+            "Tried to connect the same output twice."
+        );
+        assert!(
+            self.input_source(input).is_none()
+                && outputs.iter().all(|&o| self.output_target(o).is_none()),
+            "Structure is already connected."
+        );
+        if let [output] = outputs {
+            self.connect(*output, input);
+            return None;
+        }
+        let tree = merger_tree(outputs.len());
+        let target = self.get_structure(input.structure_id);
+        let near = Position {
+            x: target.pos.x - tree.size.w - 1,
+            y: target.pos.y,
+        };
+        let pos = self.find_room(&tree.contents, near);
+        let tree = self.place(&tree, pos.x, pos.y);
+        for (i, &output) in outputs.iter().enumerate() {
+            self.connect(output, tree.input(i));
+        }
+        self.connect(tree.output(0), input);
+        Some(tree)
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::VecDeque;
+
+use super::*;
+
+impl World {
+    /// indices of the structures each structure feeds, once per wire.
+    fn successors(&self) -> Vec<Vec<usize>> {
+        self.structures
+            .iter()
+            .map(|s| {
+                s.structure
+                    .get_outputs()
+                    .iter()
+                    .filter_map(|port| Some(port.target?.structure_index))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn handles_of(&self, indices: impl IntoIterator<Item = usize>) -> Vec<Structure> {
+        indices
+            .into_iter()
+            .map(|index| self.structure_handle(index))
+            .collect()
+    }
+
+    /// groups of structures that can all reach each other by following connections downstream.
+    /// a structure on no loop is in a group by itself.
+    ///
+    /// groups come out downstream first: nothing in a group feeds anything in a later group.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<Structure>> {
+        // tarjan's, without recursion so huge factories don't blow the stack
+        let successors = self.successors();
+        let n = successors.len();
+        let mut order = vec![usize::MAX; n];
+        let mut low = vec![0; n];
+        let mut on_stack = vec![false; n];
+        let mut stack = vec![];
+        let mut components = vec![];
+        let mut next = 0;
+        for root in 0..n {
+            if order[root] != usize::MAX {
+                continue;
+            }
+            // (node, how many of its successors have been looked at)
+            let mut path = vec![(root, 0)];
+            order[root] = next;
+            low[root] = next;
+            next += 1;
+            stack.push(root);
+            on_stack[root] = true;
+            while let Some(&mut (node, ref mut seen)) = path.last_mut() {
+                if let Some(&child) = successors[node].get(*seen) {
+                    *seen += 1;
+                    if order[child] == usize::MAX {
+                        order[child] = next;
+                        low[child] = next;
+                        next += 1;
+                        stack.push(child);
+                        on_stack[child] = true;
+                        path.push((child, 0));
+                    } else if on_stack[child] {
+                        low[node] = low[node].min(order[child]);
+                    }
+                    continue;
+                }
+                path.pop();
+                if let Some(&(parent, _)) = path.last() {
+                    low[parent] = low[parent].min(low[node]);
+                }
+                if low[node] == order[node] {
+                    let mut component = vec![];
+                    loop {
+                        let member = stack.pop().unwrap();
+                        on_stack[member] = false;
+                        component.push(member);
+                        if member == node {
+                            break;
+                        }
+                    }
+                    component.sort_unstable();
+                    components.push(self.handles_of(component));
+                }
+            }
+        }
+        components
+    }
+
+    /// every feedback loop, as the group of structures caught up in it.
+    /// empty if things only ever flow one way.
+    pub fn cycles(&self) -> Vec<Vec<Structure>> {
+        let successors = self.successors();
+        self.strongly_connected_components()
+            .into_iter()
+            .filter(|component| {
+                component.len() > 1 || successors[component[0].index].contains(&component[0].index)
+            })
+            .collect()
+    }
+
+    /// groups of structures connected to each other at all, whichever way things flow.
+    /// each one is a production line that could be moved around, or put in its own file,
+    /// without cutting any wires. unconnected structures are in a group by themselves.
+    ///
+    /// groups are ordered by their first structure, and each group is in index order.
+    pub fn weakly_connected_components(&self) -> Vec<Vec<Structure>> {
+        let n = self.structures.len();
+        let mut parent: Vec<usize> = (0..n).collect();
+        fn root(parent: &mut [usize], mut node: usize) -> usize {
+            while parent[node] != node {
+                parent[node] = parent[parent[node]];
+                node = parent[node];
+            }
+            node
+        }
+        for (node, successors) in self.successors().into_iter().enumerate() {
+            for successor in successors {
+                let (a, b) = (root(&mut parent, node), root(&mut parent, successor));
+                parent[a.max(b)] = a.min(b);
+            }
+        }
+        let mut groups: Vec<Vec<usize>> = vec![];
+        let mut group_of = vec![usize::MAX; n];
+        for node in 0..n {
+            let root = root(&mut parent, node);
+            if group_of[root] == usize::MAX {
+                group_of[root] = groups.len();
+                groups.push(vec![]);
+            }
+            groups[group_of[root]].push(node);
+        }
+        groups
+            .into_iter()
+            .map(|group| self.handles_of(group))
+            .collect()
+    }
+
+    /// every structure, each one after everything that feeds it.
+    /// `None` if there's a loop, since then there's no such order. see [`World::cycles`].
+    pub fn topological_order(&self) -> Option<Vec<Structure>> {
+        let successors = self.successors();
+        let mut feeders = vec![0; successors.len()];
+        for &successor in successors.iter().flatten() {
+            feeders[successor] += 1;
+        }
+        let mut ready: VecDeque<usize> = (0..successors.len())
+            .filter(|&node| feeders[node] == 0)
+            .collect();
+        let mut order = vec![];
+        while let Some(node) = ready.pop_front() {
+            order.push(node);
+            for &successor in &successors[node] {
+                feeders[successor] -= 1;
+                if feeders[successor] == 0 {
+                    ready.push_back(successor);
+                }
+            }
+        }
+        (order.len() == successors.len()).then(|| self.handles_of(order))
+    }
+
+    /// for each structure, in index order, the most connections between it and
+    /// something that isn't fed by anything. structures at the very start are 0.
+    /// `None` if there's a loop.
+    pub fn depths(&self) -> Option<Vec<usize>> {
+        let order = self.topological_order()?;
+        let successors = self.successors();
+        let mut depths = vec![0; successors.len()];
+        for structure in order {
+            let depth = depths[structure.index];
+            for &successor in &successors[structure.index] {
+                depths[successor] = depths[successor].max(depth + 1);
+            }
+        }
+        Some(depths)
+    }
+
+    /// how many connections the longest chain of machines has.
+    /// 0 for a world without connections, `None` if there's a loop.
+    pub fn longest_path(&self) -> Option<usize> {
+        Some(self.depths()?.into_iter().max().unwrap_or(0))
+    }
+}
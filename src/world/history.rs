@@ -0,0 +1,229 @@
+use std::{
+    cmp::Ordering as CmpOrdering,
+    hash::{Hash, Hasher},
+    mem,
+};
+
+use super::*;
+
+/// something [`World::undo`] can take back.
+#[derive(Debug, Clone)]
+enum Edit {
+    /// `count` structures went onto the end of the world.
+    /// while undone, they wait in `undone` to be put back.
+    Place {
+        count: usize,
+        undone: Vec<PositionedStructureData>,
+    },
+    Connect(PortOut, PortIn),
+    /// a whole [`World::transaction`], taken back all at once.
+    Batch(Vec<Edit>),
+}
+
+impl Edit {
+    fn undo(&mut self, world: &mut World) {
+        match self {
+            Self::Place { count, undone } => {
+                let start = world.structures.len() - *count;
+                *undone = world.structures.split_off(start);
+            }
+            &mut Self::Connect(source, destination) => {
+                world.structures[source.structure_id.index]
+                    .structure
+                    .get_outputs_mut()[source.index as usize]
+                    .target = None;
+                world.structures[destination.structure_id.index]
+                    .structure
+                    .get_inputs_mut()[destination.index as usize]
+                    .target = None;
+            }
+            Self::Batch(edits) => {
+                for edit in edits.iter_mut().rev() {
+                    edit.undo(world);
+                }
+            }
+        }
+    }
+
+    fn redo(&mut self, world: &mut World) {
+        match self {
+            Self::Place { undone, .. } => world.structures.append(undone),
+            &mut Self::Connect(source, destination) => world.link(source, destination),
+            Self::Batch(edits) => {
+                for edit in edits {
+                    edit.redo(world);
+                }
+            }
+        }
+    }
+}
+
+/// what [`World::place`] and [`World::connect`] did, for [`World::undo`].
+///
+/// it's bookkeeping, not part of what the world is, so two worlds with the same structures
+/// are equal no matter how they got there.
+#[derive(Clone, Default)]
+pub(crate) struct History {
+    done: Vec<Edit>,
+    undone: Vec<Edit>,
+    /// how many [`World::transaction`]s are running, which undo and redo would pull the
+    /// history out from under.
+    transactions: usize,
+}
+
+impl History {
+    fn push(&mut self, edit: Edit) {
+        self.done.push(edit);
+        self.undone.clear();
+    }
+}
+
+impl fmt::Debug for History {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("History")
+            .field("done", &self.done.len())
+            .field("undone", &self.undone.len())
+            .field("transactions", &self.transactions)
+            .finish()
+    }
+}
+
+impl PartialEq for History {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for History {}
+
+impl PartialOrd for History {
+    fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for History {
+    fn cmp(&self, _other: &Self) -> CmpOrdering {
+        CmpOrdering::Equal
+    }
+}
+
+impl Hash for History {
+    fn hash<H: Hasher>(&self, _state: &mut H) {}
+}
+
+impl World {
+    pub(crate) fn record_place(&mut self, count: usize) {
+        if count > 0 {
+            self.history.push(Edit::Place {
+                count,
+                undone: vec![],
+            });
+        }
+    }
+
+    pub(crate) fn record_connect(&mut self, source: PortOut, destination: PortIn) {
+        self.history.push(Edit::Connect(source, destination));
+    }
+
+    /// takes back the last [`World::place`], [`World::connect`] or [`World::transaction`].
+    /// returns false if there's nothing left to undo.
+    ///
+    /// only those are kept track of, so don't mix this with editing `structures` by hand.
+    /// handles to undone structures stop being any good.
+    ///
+    /// panics if called from inside a [`World::transaction`].
+    pub fn undo(&mut self) -> bool {
+        assert!(
+            self.history.transactions == 0,
+            "Cannot undo in the middle of a transaction."
+        );
+        let Some(mut edit) = self.history.done.pop() else {
+            return false;
+        };
+        edit.undo(self);
+        self.history.undone.push(edit);
+        true
+    }
+
+    /// puts back the last thing [`World::undo`] took back, with the same indices as before.
+    /// anything new since then clears what can be redone.
+    /// returns false if there's nothing to redo.
+    ///
+    /// panics if called from inside a [`World::transaction`].
+    pub fn redo(&mut self) -> bool {
+        assert!(
+            self.history.transactions == 0,
+            "Cannot redo in the middle of a transaction."
+        );
+        let Some(mut edit) = self.history.undone.pop() else {
+            return false;
+        };
+        edit.redo(self);
+        self.history.done.push(edit);
+        true
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.history.done.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.history.undone.is_empty()
+    }
+
+    /// forgets everything there is to undo or redo.
+    pub fn clear_history(&mut self) {
+        self.history = History {
+            transactions: self.history.transactions,
+            ..History::default()
+        };
+    }
+
+    /// runs `edit`, and if it fails, puts the world back the way it was, as if nothing happened.
+    /// if it works, everything it did is undone in one go by [`World::undo`].
+    ///
+    /// combine with [`World::try_place`], [`World::paste`] and friends to try out a layout
+    /// without blowing up halfway through:
+    /// ```
+    /// # use immortality_factory_laboratory::prelude::*;
+    /// let mut world = World::new();
+    /// world.place(Refinery, 3, 0);
+    /// let result = world.transaction(|w| {
+    ///     w.try_place(AirPump, 0, 0)?;
+    ///     w.try_place(AirPump, 2, 0)?; // lands on the refinery
+    ///     Ok::<_, Collision>(())
+    /// });
+    /// assert!(result.is_err());
+    /// assert_eq!(world.structures.len(), 1);
+    /// ```
+    /// [`World::undo`] and [`World::redo`] panic inside `edit`, since there'd be no telling
+    /// what to take back if it fails.
+    /// panics still go straight through, and leave the world however they found it.
+    pub fn transaction<T, E>(
+        &mut self,
+        edit: impl FnOnce(&mut Self) -> Result<T, E>,
+    ) -> Result<T, E> {
+        let structures = self.structures.clone();
+        let start = self.history.done.len();
+        let undone = mem::take(&mut self.history.undone);
+        self.history.transactions += 1;
+        let result = edit(self);
+        self.history.transactions -= 1;
+        match result {
+            Ok(_) if self.history.done.len() == start => self.history.undone = undone,
+            Ok(_) => {
+                let start = start.min(self.history.done.len());
+                let edits = self.history.done.split_off(start);
+                self.history.done.push(Edit::Batch(edits));
+            }
+            Err(_) => {
+                // a copy rather than undoing, so it also takes back edits to `structures`
+                self.structures = structures;
+                self.history.done.truncate(start);
+                self.history.undone = undone;
+            }
+        }
+        result
+    }
+}
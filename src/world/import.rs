@@ -0,0 +1,278 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{self, Read},
+};
+
+use super::*;
+use crate::structure::{PortInRaw, PortOutRaw};
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+fn number(value: &str) -> io::Result<f64> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| invalid(format!("expected a number, got {value:?}")))
+}
+
+/// one entry of a machine's `+output_list+` or `+input_list+`.
+struct PortEntry {
+    index: usize,
+    item: Item,
+    /// `connected_machine`, which is `100000 + id` of the other end, or -1 for nothing.
+    target_id: i64,
+    target_port: u8,
+    target_x: Coord,
+    target_y: Coord,
+}
+
+impl PortEntry {
+    fn parse(entry: &str) -> io::Result<Self> {
+        let mut fields = HashMap::new();
+        for field in entry.split(',') {
+            let (key, value) = field
+                .split_once(':')
+                .ok_or_else(|| invalid(format!("malformed port field {field:?}")))?;
+            fields.insert(key.trim_matches('+'), value);
+        }
+        let field = |key: &str| {
+            fields
+                .get(key)
+                .ok_or_else(|| invalid(format!("port entry is missing {key}")))
+                .and_then(|v| number(v))
+        };
+        let item = field("content")? as i8;
+        Ok(Self {
+            index: field("index")? as usize,
+            item: Item::try_from(item).map_err(|_| invalid(format!("unknown item id {item}")))?,
+            target_id: field("connected_machine")? as i64,
+            target_port: field("connected_machine_slot_index")? as u8,
+            target_x: field("connected_machine_x")? as Coord,
+            target_y: field("connected_machine_y")? as Coord,
+        })
+    }
+
+    /// parses the list that follows `+{name}+:[` in a struct blob.
+    fn parse_list(blob: &str, name: &str) -> io::Result<Vec<Self>> {
+        let key = format!("+{name}+:[");
+        let start = blob
+            .find(&key)
+            .ok_or_else(|| invalid(format!("machine has no {name}")))?
+            + key.len();
+        let len = blob[start..]
+            .find(']')
+            .ok_or_else(|| invalid(format!("unterminated {name}")))?;
+        blob[start..start + len]
+            .split('}')
+            .map(|entry| entry.trim_start_matches(',').trim_start_matches('{'))
+            .filter(|entry| !entry.is_empty())
+            .map(Self::parse)
+            .collect()
+    }
+}
+
+/// everything the save says about one machine, before connections are resolved.
+#[derive(Default)]
+struct MachineEntry {
+    kind: Option<StructureKind>,
+    x: Option<Coord>,
+    y: Option<Coord>,
+    outputs: Vec<PortEntry>,
+    inputs: Vec<PortEntry>,
+    storage: BTreeMap<usize, Item>,
+}
+
+/// the game stores positions in pixels, 22 to a tile.
+fn tile(value: &str) -> io::Result<Coord> {
+    Ok((number(value)? / 22.0).round() as Coord)
+}
+
+/// a save file taken apart.
+pub(crate) struct ParsedSave {
+    pub(crate) world: World,
+    /// everything up to and including the `[Machines]` header.
+    pub(crate) before: String,
+    /// everything from the section after `[Machines]` onwards.
+    pub(crate) after: String,
+    /// per-machine lines we don't model, as `(structure index, line without the id)`.
+    pub(crate) extra: Vec<(usize, String)>,
+}
+
+impl ParsedSave {
+    pub(crate) fn parse(save: &str) -> io::Result<Self> {
+        let mut before = String::new();
+        let mut after = String::new();
+        let mut extra = vec![];
+        let mut machines = BTreeMap::<usize, MachineEntry>::new();
+        let mut section = "";
+        let mut seen_machines = false;
+        for raw_line in save.split_inclusive('\n') {
+            let line = raw_line.trim();
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = name;
+                seen_machines |= name == "Machines";
+            }
+            if section != "Machines" {
+                if seen_machines {
+                    &mut after
+                } else {
+                    &mut before
+                }
+                .push_str(raw_line);
+                continue;
+            }
+            if line == "[Machines]" {
+                before.push_str(raw_line);
+                continue;
+            }
+            if line.is_empty() {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| invalid(format!("expected key=value, got {line:?}")))?;
+            let value = value.trim_matches('"');
+            let Some((id, field)) = key.split_once('-') else {
+                // `total`, which we can count ourselves
+                continue;
+            };
+            let id = id
+                .parse()
+                .map_err(|_| invalid(format!("bad machine id in {key:?}")))?;
+            let machine = machines.entry(id).or_default();
+            match field {
+                "struct" => {
+                    machine.outputs = PortEntry::parse_list(value, "output_list")?;
+                    machine.inputs = PortEntry::parse_list(value, "input_list")?;
+                }
+                "x" => machine.x = Some(tile(value)?),
+                "y" => machine.y = Some(tile(value)?),
+                "object" => {
+                    let number = number(value)? as u8;
+                    machine.kind = Some(
+                        StructureKind::from_object_number(number)
+                            .ok_or_else(|| invalid(format!("unknown object number {number}")))?,
+                    );
+                }
+                _ => {
+                    let Some(slot) = field.strip_prefix("storage_load_at ") else {
+                        // something we don't model, kept around to be written back out
+                        extra.push((id, line.split_once('-').unwrap().1.to_owned()));
+                        continue;
+                    };
+                    let slot = slot
+                        .parse()
+                        .map_err(|_| invalid(format!("bad storage slot in {key:?}")))?;
+                    let item = number(value)? as i8;
+                    let item = Item::try_from(item)
+                        .map_err(|_| invalid(format!("unknown item id {item}")))?;
+                    machine.storage.insert(slot, item);
+                }
+            }
+        }
+
+        let mut world = World::new();
+        let ids: HashMap<usize, usize> = machines
+            .keys()
+            .enumerate()
+            .map(|(index, &id)| (id, index))
+            .collect();
+        for (id, machine) in &machines {
+            let missing = |what| invalid(format!("machine {id} has no {what}"));
+            let kind = machine.kind.ok_or_else(|| missing("object"))?;
+            let pos = Position {
+                x: machine.x.ok_or_else(|| missing("x"))?,
+                y: machine.y.ok_or_else(|| missing("y"))?,
+            };
+            world.structures.push(PositionedStructureData {
+                pos,
+                structure: StructureDataFull::from(kind),
+            });
+        }
+
+        // every machine is in place, so connections can be resolved now
+        let find_target = |world: &World, entry: &PortEntry| -> io::Result<Option<usize>> {
+            if entry.target_id < 0 {
+                return Ok(None);
+            }
+            let by_id = usize::try_from(entry.target_id - 100000)
+                .ok()
+                .and_then(|id| ids.get(&id).copied());
+            let by_pos = || {
+                world
+                    .structures
+                    .iter()
+                    .position(|s| s.pos.world_coords() == (entry.target_x, entry.target_y))
+            };
+            by_id.or_else(by_pos).map(Some).ok_or_else(|| {
+                invalid(format!(
+                    "connection to machine {} at ({}, {}) goes nowhere",
+                    entry.target_id, entry.target_x, entry.target_y
+                ))
+            })
+        };
+        for (index, machine) in machines.values().enumerate() {
+            for entry in &machine.outputs {
+                let target = find_target(&world, entry)?.map(|structure_index| PortInRaw {
+                    structure_index,
+                    port: entry.target_port,
+                });
+                let structure = &mut world.structures[index].structure;
+                let kind = structure.kind();
+                let port = structure
+                    .get_outputs_mut()
+                    .get_mut(entry.index)
+                    .ok_or_else(|| invalid(format!("{kind:?} has no output #{}", entry.index)))?;
+                port.item = entry.item;
+                port.target = target;
+            }
+            for entry in &machine.inputs {
+                let target = find_target(&world, entry)?.map(|structure_index| PortOutRaw {
+                    structure_index,
+                    port: entry.target_port,
+                });
+                let structure = &mut world.structures[index].structure;
+                let kind = structure.kind();
+                let port = structure
+                    .get_inputs_mut()
+                    .get_mut(entry.index)
+                    .ok_or_else(|| invalid(format!("{kind:?} has no input #{}", entry.index)))?;
+                port.item = entry.item;
+                port.target = target;
+            }
+            let structure = &mut world.structures[index].structure;
+            let kind = structure.kind();
+            for (&slot, &item) in &machine.storage {
+                *structure
+                    .get_storage_mut()
+                    .get_mut(slot)
+                    .ok_or_else(|| invalid(format!("{kind:?} has no storage slot #{slot}")))? =
+                    item;
+            }
+        }
+        for (id, _) in &mut extra {
+            *id = ids[id];
+        }
+        Ok(Self {
+            world,
+            before,
+            after,
+            extra,
+        })
+    }
+}
+
+impl World {
+    /// reads the machines back out of a save file, like one written by [`World::export`].
+    /// everything outside of `[Machines]` is ignored, see [`Save`] to keep it.
+    ///
+    /// connections are matched up by the `100000 + id` numbering the game uses,
+    /// falling back to the coordinates of the other end if that doesn't point anywhere.
+    pub fn import(f: &mut impl Read) -> io::Result<Self> {
+        let mut save = String::new();
+        f.read_to_string(&mut save)?;
+        Ok(ParsedSave::parse(&save)?.world)
+    }
+}
@@ -1,35 +1,99 @@
 pub mod blueprint;
+mod compact;
+mod diff;
+mod fan;
+mod graph;
+mod history;
+mod import;
+mod query;
+mod render;
+mod save;
+mod stats;
+mod wires;
+
+pub use diff::{Moved, Slot, SlotChange, WorldDiff};
+pub use save::Save;
+pub use wires::{Wire, WireStats};
 
 use std::{
+    error::Error,
+    fmt::{self, Display},
     io::{self, Write},
     num::NonZeroU32,
     ops::{Add, Mul},
-    sync::Mutex,
+    sync::atomic::{AtomicU32, Ordering},
 };
 
 use crate::{prelude::*, structure::StructureDataFull};
+use history::History;
 // use super::structure::{StructureData, StructureKind};
 
 type ID = NonZeroU32;
-static WORLD_COUNT: Mutex<ID> = Mutex::new(NonZeroU32::new(1).unwrap());
+static WORLD_COUNT: WorldIds = WorldIds::new();
 fn new_world_id() -> WorldId {
     // could be a UUID instead of an incrementing count
-    let mut guard = WORLD_COUNT
-        .lock()
-        .expect("Failed to lock global WORLD_COUNT");
-    let id = *guard;
-    *guard = guard
-        .checked_add(1)
-        .expect("How? You have more than u32::MAX worlds?");
-    WorldId { id }
+    WORLD_COUNT.next()
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WorldId {
     id: ID,
 }
 
+impl WorldId {
+    pub const fn new(id: NonZeroU32) -> Self {
+        Self { id }
+    }
+
+    pub const fn get(&self) -> NonZeroU32 {
+        self.id
+    }
+}
+
+/// hands out world ids, counting up from 1.
+///
+/// [`World::new`] draws from a global one, so its ids depend on how many worlds
+/// were made before, in whatever order the threads got there.
+/// make your own and use [`WorldIds::world`] for ids that come out the same every run.
+/// it's lock free, so it can be shared between threads without them waiting on each other.
+#[derive(Debug)]
+pub struct WorldIds {
+    next: AtomicU32,
+}
+
+impl Default for WorldIds {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WorldIds {
+    pub const fn new() -> Self {
+        Self::starting_at(NonZeroU32::MIN)
+    }
+
+    pub const fn starting_at(first: NonZeroU32) -> Self {
+        Self {
+            next: AtomicU32::new(first.get()),
+        }
+    }
+
+    pub fn next(&self) -> WorldId {
+        let id = self
+            .next
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |id| id.checked_add(1))
+            .expect("How? You have more than u32::MAX worlds?");
+        WorldId::new(NonZeroU32::new(id).expect("ids start above 0 and never wrap"))
+    }
+
+    pub fn world(&self) -> World {
+        World::with_id(self.next())
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Position {
     pub x: Coord,
     pub y: Coord,
@@ -50,6 +114,7 @@ impl Position {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Offset {
     pub x: Coord,
     pub y: Coord,
@@ -97,19 +162,69 @@ impl Add<Offset> for PositionedStructureData {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PositionedStructureData {
     pub pos: Position,
     pub structure: StructureDataFull,
 }
 
+/// two structures trying to take up the same tiles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Collision {
+    pub new_kind: StructureKind,
+    pub new_pos: Position,
+    pub old_kind: StructureKind,
+    pub old_pos: Position,
+}
+
+impl Collision {
+    fn new(new: &PositionedStructureData, old: &PositionedStructureData) -> Self {
+        Self {
+            new_kind: new.structure.kind(),
+            new_pos: new.pos,
+            old_kind: old.structure.kind(),
+            old_pos: old.pos,
+        }
+    }
+}
+
+impl Display for Collision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} at ({}, {}) would overlap {:?} at ({}, {})",
+            self.new_kind,
+            self.new_pos.x,
+            self.new_pos.y,
+            self.old_kind,
+            self.old_pos.x,
+            self.old_pos.y
+        )
+    }
+}
+
+impl Error for Collision {}
+
 /// technically only the index is necessary. the rest are for debug assertions.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Structure {
     pub(crate) world_id: WorldId,
     pub(crate) index: usize,
     pub(crate) kind: StructureKind,
 }
 
+impl Structure {
+    pub fn kind(&self) -> StructureKind {
+        self.kind
+    }
+
+    /// where it is in [`World::structures`].
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
 impl HasSize for Structure {
     fn size(&self) -> Size {
         self.kind.size()
@@ -153,18 +268,41 @@ impl Machine for Structure {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PortIn {
     pub(crate) structure_id: Structure,
     pub(crate) index: u8,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PortOut {
     pub(crate) structure_id: Structure,
     pub(crate) index: u8,
 }
 
+impl PortIn {
+    pub fn structure(&self) -> Structure {
+        self.structure_id
+    }
+
+    pub fn index(&self) -> usize {
+        self.index as usize
+    }
+}
+
+impl PortOut {
+    pub fn structure(&self) -> Structure {
+        self.structure_id
+    }
+
+    pub fn index(&self) -> usize {
+        self.index as usize
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Size {
     pub w: Coord,
     pub h: Coord,
@@ -259,38 +397,81 @@ impl Placeable for StructureKind {
     }
 }
 
+/// NOTE: with the `serde` feature, a world is stored without its id, and gets a fresh one from
+/// [`World::new`]'s counter when it comes back, since ids from another run mean nothing here.
+/// so handles into the world from before it was stored don't work on what comes back.
+/// a [`Blueprint`]'s own ports are moved over to the new id for you.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct World {
+    #[cfg_attr(feature = "serde", serde(skip, default = "new_world_id"))]
     world_id: WorldId,
     pub structures: Vec<PositionedStructureData>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    history: History,
+}
+
+impl Default for World {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl World {
     pub fn new() -> Self {
+        Self::with_id(new_world_id())
+    }
+
+    /// a world with a specific id.
+    /// nothing stops it from sharing its id with another world, which would let structures
+    /// from one be used in the other without tripping the id checks, so don't mix ids from
+    /// different [`WorldIds`] (or the global one behind [`World::new`]) in the same layout.
+    pub fn with_id(world_id: WorldId) -> Self {
         Self {
-            world_id: new_world_id(),
+            world_id,
             structures: vec![],
+            history: History::default(),
+        }
+    }
+
+    pub fn id(&self) -> WorldId {
+        self.world_id
+    }
+
+    /// builds a handle for the structure at `index`. panics if there is none.
+    pub(crate) fn structure_handle(&self, index: usize) -> Structure {
+        Structure {
+            world_id: self.world_id,
+            index,
+            kind: self.structures[index].structure.kind(),
         }
     }
 
     // WARNING: Structure collision detection. O(n) time complexity.
     // could, with difficulty, implement a chunk-based system.
-    // ideally with some sort of stack trace that traces a building's exact blueprint.
-    fn assert_no_structure_collision(&self, structure: &PositionedStructureData) {
+    pub(crate) fn find_structure_collision(
+        &self,
+        structure: &PositionedStructureData,
+    ) -> Option<&PositionedStructureData> {
         let nl = structure.pos.x;
         let nr = nl + structure.structure.width();
         let nt = structure.pos.y;
         let nb = nt + structure.structure.height();
 
-        for old in &self.structures {
+        self.structures.iter().find(|old| {
             let ol = old.pos.x;
             let or = ol + old.structure.width();
             let ot = old.pos.y;
             let ob = ot + old.structure.height();
 
-            let collision = nb > ot && ob > nt && nr > ol && or > nl;
-            assert!(
-                !collision,
+            nb > ot && ob > nt && nr > ol && or > nl
+        })
+    }
+
+    // ideally with some sort of stack trace that traces a building's exact blueprint.
+    fn assert_no_structure_collision(&self, structure: &PositionedStructureData) {
+        if let Some(old) = self.find_structure_collision(structure) {
+            panic!(
                 "Structure collision detected.\n\
                 - new: {structure:#?}\n\
                 - old: {old:#?}"
@@ -300,7 +481,51 @@ impl World {
 
     // TODO: collision detection
     pub fn place<P: Placeable>(&mut self, object: P, x: Coord, y: Coord) -> P::Id {
-        object.place_in(self, x, y)
+        let count = self.structures.len();
+        let id = object.place_in(self, x, y);
+        self.record_place(self.structures.len() - count);
+        id
+    }
+
+    /// [`World::place`] for a single structure, but gives back what it would land on
+    /// instead of panicking.
+    pub fn try_place(
+        &mut self,
+        structure: impl Into<StructureDataFull>,
+        x: Coord,
+        y: Coord,
+    ) -> Result<Structure, Collision> {
+        let new = PositionedStructureData {
+            pos: Position { x, y },
+            structure: structure.into(),
+        };
+        if let Some(old) = self.find_structure_collision(&new) {
+            return Err(Collision::new(&new, old));
+        }
+        Ok(self.place(new.structure, x, y))
+    }
+
+    /// pastes `blueprint` in at `(x, y)`, after making sure it won't land on anything
+    /// that's already there. nothing is pasted if it would.
+    pub fn paste(
+        &mut self,
+        blueprint: &World,
+        x: Coord,
+        y: Coord,
+    ) -> Result<PastedWorld, Collision> {
+        self.check_paste(blueprint, x, y)?;
+        Ok(self.place(blueprint, x, y))
+    }
+
+    /// [`World::paste`], for blueprints.
+    pub fn paste_blueprint(
+        &mut self,
+        blueprint: &Blueprint,
+        x: Coord,
+        y: Coord,
+    ) -> Result<PastedBlueprint, Collision> {
+        self.check_paste(&blueprint.contents, x, y)?;
+        Ok(self.place(blueprint, x, y))
     }
 
     pub fn get_structure(&self, structure: Structure) -> &PositionedStructureData {
@@ -343,10 +568,40 @@ impl World {
                 .is_none(),
             "Structure is already connected."
         );
-        self.structures[src].structure.get_outputs_mut()[source.index as usize].target =
-            Some(destination.into());
-        self.structures[dst].structure.get_inputs_mut()[destination.index as usize].target =
-            Some(source.into());
+        self.link(source, destination);
+        self.record_connect(source, destination);
+    }
+
+    /// [`World::connect`], without the checks or the history.
+    fn link(&mut self, source: PortOut, destination: PortIn) {
+        self.structures[source.structure_id.index]
+            .structure
+            .get_outputs_mut()[source.index as usize]
+            .target = Some(destination.into());
+        self.structures[destination.structure_id.index]
+            .structure
+            .get_inputs_mut()[destination.index as usize]
+            .target = Some(source.into());
+    }
+
+    /// where an output leads, if anywhere.
+    pub fn output_target(&self, port: PortOut) -> Option<PortIn> {
+        let structure = self.get_structure(port.structure_id);
+        let target = structure.structure.get_outputs()[port.index as usize].target?;
+        Some(PortIn {
+            structure_id: self.structure_handle(target.structure_index),
+            index: target.port,
+        })
+    }
+
+    /// where an input comes from, if anywhere.
+    pub fn input_source(&self, port: PortIn) -> Option<PortOut> {
+        let structure = self.get_structure(port.structure_id);
+        let source = structure.structure.get_inputs()[port.index as usize].target?;
+        Some(PortOut {
+            structure_id: self.structure_handle(source.structure_index),
+            index: source.port,
+        })
     }
 
     pub fn connect_all(&mut self, connections: impl IntoIterator<Item = (PortOut, PortIn)>) {
@@ -355,6 +610,23 @@ impl World {
         }
     }
 
+    /// the body of the `[Machines]` section.
+    /// `extra` holds lines to tack onto each machine, as `(structure index, line without the id)`.
+    pub(crate) fn export_machines(
+        &self,
+        f: &mut impl Write,
+        extra: &[(usize, String)],
+    ) -> io::Result<()> {
+        for (i, PositionedStructureData { pos, structure }) in self.structures.iter().enumerate() {
+            structure.export(f, self, i, pos.x, pos.y)?;
+            for (_, line) in extra.iter().filter(|(index, _)| *index == i) {
+                writeln!(f, "{i}-{line}")?;
+            }
+        }
+        let structure_count = self.structures.len();
+        writeln!(f, r#"total="{structure_count}.000000""#)
+    }
+
     pub fn export(&self, f: &mut impl Write) -> io::Result<()> {
         writeln!(
             f,
@@ -363,14 +635,10 @@ impl World {
 [Machines]"#
         )?;
 
-        for (i, PositionedStructureData { pos, structure }) in self.structures.iter().enumerate() {
-            structure.export(f, self, i, pos.x, pos.y)?;
-        }
-        let structure_count = self.structures.len();
+        self.export_machines(f, &[])?;
         writeln!(
             f,
-            r#"total="{structure_count}.000000"
-[Machine-Storage]
+            r#"[Machine-Storage]
 13-cur="999.000000"
 12-cur="999.000000"
 11-cur="999.000000"
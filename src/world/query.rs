@@ -0,0 +1,74 @@
+use super::*;
+
+impl World {
+    /// a handle to every structure, in order.
+    pub fn handles(&self) -> impl Iterator<Item = Structure> + '_ {
+        (0..self.structures.len()).map(|index| self.structure_handle(index))
+    }
+
+    pub fn structures_of_kind(&self, kind: StructureKind) -> impl Iterator<Item = Structure> + '_ {
+        self.handles().filter(move |s| s.kind == kind)
+    }
+
+    /// every structure with at least one tile inside the `size` box with its top left at `pos`.
+    pub fn structures_in(&self, pos: Position, size: Size) -> impl Iterator<Item = Structure> + '_ {
+        let (l, t) = (pos.x, pos.y);
+        let (r, b) = (l + size.w, t + size.h);
+        self.handles().filter(move |&s| {
+            let data = self.get_structure(s);
+            let (sl, st) = (data.pos.x, data.pos.y);
+            let (sr, sb) = (sl + s.width(), st + s.height());
+            sl < r && l < sr && st < b && t < sb
+        })
+    }
+
+    /// whatever covers the tile at `(x, y)`, if anything.
+    pub fn structure_at(&self, x: Coord, y: Coord) -> Option<Structure> {
+        self.structures_in(Position { x, y }, Size { w: 1, h: 1 })
+            .next()
+    }
+
+    /// where each connected output of `structure` goes.
+    pub fn downstream_ports(
+        &self,
+        structure: Structure,
+    ) -> impl Iterator<Item = (PortOut, PortIn)> + '_ {
+        let outputs = self.get_structure(structure).structure.get_outputs().len();
+        (0..outputs).filter_map(move |index| {
+            let port = PortOut {
+                structure_id: structure,
+                index: index as u8,
+            };
+            Some((port, self.output_target(port)?))
+        })
+    }
+
+    /// where each connected input of `structure` comes from.
+    pub fn upstream_ports(
+        &self,
+        structure: Structure,
+    ) -> impl Iterator<Item = (PortOut, PortIn)> + '_ {
+        let inputs = self.get_structure(structure).structure.get_inputs().len();
+        (0..inputs).filter_map(move |index| {
+            let port = PortIn {
+                structure_id: structure,
+                index: index as u8,
+            };
+            Some((self.input_source(port)?, port))
+        })
+    }
+
+    /// structures fed by `structure`, once per wire.
+    /// something connected twice shows up twice.
+    pub fn downstream(&self, structure: Structure) -> impl Iterator<Item = Structure> + '_ {
+        self.downstream_ports(structure)
+            .map(|(_, input)| input.structure_id)
+    }
+
+    /// structures feeding `structure`, once per wire.
+    /// something connected twice shows up twice.
+    pub fn upstream(&self, structure: Structure) -> impl Iterator<Item = Structure> + '_ {
+        self.upstream_ports(structure)
+            .map(|(output, _)| output.structure_id)
+    }
+}
@@ -0,0 +1,74 @@
+use super::*;
+
+impl World {
+    /// top left corner and size of the smallest box holding every structure.
+    /// `None` for an empty world.
+    pub fn bounds(&self) -> Option<(Position, Size)> {
+        let mut structures = self.structures.iter();
+        let first = structures.next()?;
+        let (mut l, mut t) = (first.pos.x, first.pos.y);
+        let (mut r, mut b) = (l + first.structure.width(), t + first.structure.height());
+        for s in structures {
+            l = l.min(s.pos.x);
+            t = t.min(s.pos.y);
+            r = r.max(s.pos.x + s.structure.width());
+            b = b.max(s.pos.y + s.structure.height());
+        }
+        Some((Position { x: l, y: t }, Size { w: r - l, h: b - t }))
+    }
+
+    /// draws the world as a grid of [symbols](StructureKind::symbol), one character per tile,
+    /// with `.` for empty ground. starts at the top left of [`World::bounds`].
+    pub fn render(&self) -> String {
+        let Some((origin, size)) = self.bounds() else {
+            return String::new();
+        };
+        let (w, h) = (size.w as usize, size.h as usize);
+        let mut grid = vec![vec!['.'; w]; h];
+        for PositionedStructureData { pos, structure } in &self.structures {
+            let x = (pos.x - origin.x) as usize;
+            let y = (pos.y - origin.y) as usize;
+            let symbol = structure.kind().symbol();
+            for row in &mut grid[y..y + structure.height() as usize] {
+                row[x..x + structure.width() as usize].fill(symbol);
+            }
+        }
+        let mut out = String::with_capacity((w + 1) * h);
+        for row in grid {
+            out.extend(row);
+            out.push('\n');
+        }
+        out
+    }
+
+    /// lists every structure with its slot items and connections, one port per line.
+    pub fn describe(&self) -> String {
+        use std::fmt::Write;
+
+        let mut out = String::new();
+        for (i, PositionedStructureData { pos, structure }) in self.structures.iter().enumerate() {
+            let kind = structure.kind();
+            // writing to a String can't fail
+            _ = writeln!(out, "#{i} {kind:?} at ({}, {})", pos.x, pos.y);
+            for (port, data) in structure.get_inputs().iter().enumerate() {
+                _ = write!(out, "  in {port} ({:?})", data.item);
+                if let Some(source) = data.target {
+                    _ = write!(out, " <- #{} out {}", source.structure_index, source.port);
+                }
+                out.push('\n');
+            }
+            for (port, data) in structure.get_outputs().iter().enumerate() {
+                _ = write!(out, "  out {port} ({:?})", data.item);
+                if let Some(target) = data.target {
+                    _ = write!(out, " -> #{} in {}", target.structure_index, target.port);
+                }
+                out.push('\n');
+            }
+            let storage = structure.get_storage();
+            if storage.iter().any(|&item| item != Empty) {
+                _ = writeln!(out, "  storage {storage:?}");
+            }
+        }
+        out
+    }
+}
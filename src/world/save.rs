@@ -0,0 +1,75 @@
+use std::io::{self, Read, Write};
+
+use super::{import::ParsedSave, *};
+
+/// a whole save file. the machines live in `world`, everything else
+/// (research, storage counters, whatever the game writes that we don't model)
+/// is kept as it was and written back out untouched.
+///
+/// use this instead of [`World::export`] to add to a factory without wiping
+/// out the hand-built parts and the game progress.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Save {
+    pub world: World,
+    before: String,
+    after: String,
+    extra: Vec<(usize, String)>,
+}
+
+impl Save {
+    pub fn import(f: &mut impl Read) -> io::Result<Self> {
+        let mut save = String::new();
+        f.read_to_string(&mut save)?;
+        let ParsedSave {
+            world,
+            mut before,
+            after,
+            extra,
+        } = ParsedSave::parse(&save)?;
+        if after.is_empty() && !before.lines().any(|line| line.trim() == "[Machines]") {
+            // no machines at all, so make room for them
+            if !before.is_empty() && !before.ends_with('\n') {
+                before.push('\n');
+            }
+            before.push_str("[Machines]\n");
+        }
+        Ok(Self {
+            world,
+            before,
+            after,
+            extra,
+        })
+    }
+
+    /// pastes `blueprint` in at `(x, y)`, after making sure it won't land on anything
+    /// that's already there. nothing is pasted if it would.
+    ///
+    /// pasted machines go after the existing ones, so nothing already in the save
+    /// gets renumbered.
+    pub fn paste(
+        &mut self,
+        blueprint: &World,
+        x: Coord,
+        y: Coord,
+    ) -> Result<PastedWorld, Collision> {
+        self.world.paste(blueprint, x, y)
+    }
+
+    /// [`Save::paste`], for blueprints.
+    pub fn paste_blueprint(
+        &mut self,
+        blueprint: &Blueprint,
+        x: Coord,
+        y: Coord,
+    ) -> Result<PastedBlueprint, Collision> {
+        self.world.paste_blueprint(blueprint, x, y)
+    }
+
+    /// writes the save back out.
+    /// machines are numbered in order, with connections renumbered to match.
+    pub fn export(&self, f: &mut impl Write) -> io::Result<()> {
+        f.write_all(self.before.as_bytes())?;
+        self.world.export_machines(f, &self.extra)?;
+        f.write_all(self.after.as_bytes())
+    }
+}
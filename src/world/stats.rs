@@ -0,0 +1,23 @@
+use std::collections::BTreeMap;
+
+use super::*;
+
+impl World {
+    /// how many of each kind of structure there are. kinds that don't show up are left out.
+    pub fn kind_counts(&self) -> BTreeMap<StructureKind, usize> {
+        let mut counts = BTreeMap::new();
+        for s in &self.structures {
+            *counts.entry(s.structure.kind()).or_default() += 1;
+        }
+        counts
+    }
+
+    /// how many connections there are, counted once per wire.
+    pub fn connection_count(&self) -> usize {
+        self.structures
+            .iter()
+            .flat_map(|s| s.structure.get_outputs())
+            .filter(|port| port.target.is_some())
+            .count()
+    }
+}
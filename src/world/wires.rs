@@ -0,0 +1,108 @@
+use super::*;
+
+/// a connection, as the game draws it: a straight line from one machine's position
+/// to the other's, the same `connected_machine_x/y` the save stores. in tiles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Wire {
+    pub output: PortOut,
+    pub input: PortIn,
+    pub from: Position,
+    pub to: Position,
+}
+
+impl Wire {
+    pub fn length(&self) -> f64 {
+        let dx = f64::from(self.to.x - self.from.x);
+        let dy = f64::from(self.to.y - self.from.y);
+        dx.hypot(dy)
+    }
+
+    /// whether the two lines cross somewhere in the middle.
+    /// wires that only touch, like two leaving the same machine, don't count,
+    /// and neither do ones lying on top of each other.
+    pub fn crosses(&self, other: &Wire) -> bool {
+        // which side of a -> b that c is on
+        fn side(a: Position, b: Position, c: Position) -> i32 {
+            let cross = i32::from(b.x - a.x) * i32::from(c.y - a.y)
+                - i32::from(b.y - a.y) * i32::from(c.x - a.x);
+            cross.signum()
+        }
+        let (a, b, c, d) = (self.from, self.to, other.from, other.to);
+        let (abc, abd) = (side(a, b, c), side(a, b, d));
+        let (cda, cdb) = (side(c, d, a), side(c, d, b));
+        abc * abd < 0 && cda * cdb < 0
+    }
+}
+
+/// how messy the wiring is, see [`World::wire_stats`].
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct WireStats {
+    pub count: usize,
+    /// in tiles.
+    pub total_length: f64,
+    pub longest: Option<Wire>,
+    /// how many pairs of wires cross.
+    pub crossings: usize,
+}
+
+impl Display for WireStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} wires, {:.1} tiles long in total",
+            self.count, self.total_length
+        )?;
+        if let Some(longest) = self.longest {
+            write!(
+                f,
+                ", longest {:.1} (#{} out {} -> #{} in {})",
+                longest.length(),
+                longest.output.structure_id.index,
+                longest.output.index,
+                longest.input.structure_id.index,
+                longest.input.index
+            )?;
+        }
+        write!(f, ", {} crossings", self.crossings)
+    }
+}
+
+impl World {
+    /// every connection, once each, in the order of the outputs they come from.
+    pub fn wires(&self) -> Vec<Wire> {
+        self.handles()
+            .flat_map(|structure| self.downstream_ports(structure))
+            .map(|(output, input)| Wire {
+                output,
+                input,
+                from: self.get_structure(output.structure_id).pos,
+                to: self.get_structure(input.structure_id).pos,
+            })
+            .collect()
+    }
+
+    /// total and longest wire length, and how many wires cross.
+    /// crossings are checked pair by pair, so this gets slow with tens of thousands of wires.
+    pub fn wire_stats(&self) -> WireStats {
+        let wires = self.wires();
+        let mut stats = WireStats {
+            count: wires.len(),
+            ..WireStats::default()
+        };
+        for (i, wire) in wires.iter().enumerate() {
+            let length = wire.length();
+            stats.total_length += length;
+            if stats
+                .longest
+                .is_none_or(|longest| length > longest.length())
+            {
+                stats.longest = Some(*wire);
+            }
+            stats.crossings += wires[i + 1..]
+                .iter()
+                .filter(|other| wire.crosses(other))
+                .count();
+        }
+        stats
+    }
+}
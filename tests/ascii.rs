@@ -0,0 +1,98 @@
+//! blueprints drawn as ascii art.
+
+use immortality_factory_laboratory::{ascii, prelude::*};
+
+fn error(source: &str) -> String {
+    match ascii::parse(source) {
+        Ok(_) => panic!("{source:?} parsed"),
+        Err(e) => e.to_string(),
+    }
+}
+
+#[test]
+fn module_doc_example() {
+    let blueprint = ascii::parse(
+        "\
+PPR1RRRR.s
+PPRRRRRR.2
+.........s
+
+1.0 -> 2.0
+input air = 1.0
+output a = 2.0
+output b = 2.1
+",
+    )
+    .unwrap();
+    let kinds: Vec<_> = blueprint
+        .contents
+        .structures
+        .iter()
+        .map(|s| s.structure.kind())
+        .collect();
+    assert_eq!(kinds, [AirPump, Refinery, Splitter]);
+    let at = |x, y| blueprint.contents.structure_at(x, y).unwrap();
+    let (pump, refinery, splitter) = (at(0, 0), at(2, 0), at(9, 0));
+    assert_eq!(pump.kind(), AirPump);
+    assert_eq!(refinery.kind(), Refinery);
+    assert_eq!(splitter.kind(), Splitter);
+    assert_eq!(blueprint.size, Size { w: 10, h: 3 });
+    assert_eq!(
+        blueprint.contents.output_target(refinery.output(0)),
+        Some(splitter.input(0))
+    );
+    assert_eq!(blueprint.names[&refinery], "1");
+    assert_eq!(blueprint.names[&splitter], "2");
+    assert!(!blueprint.names.contains_key(&pump));
+    assert_eq!(blueprint.inputs, ["air"]);
+    assert_eq!(blueprint.blueprint.inputs, [refinery.input(0)]);
+    assert_eq!(blueprint.output_index("b"), Some(1));
+}
+
+#[test]
+fn one_by_one_takes_the_digit_after_it() {
+    let blueprint = ascii::parse("O4.O\n").unwrap();
+    let storage = blueprint.contents.structure_at(0, 0).unwrap();
+    assert_eq!(blueprint.contents.structures.len(), 2);
+    assert_eq!(blueprint.names[&storage], "4");
+    assert_eq!(blueprint.names.len(), 1);
+}
+
+#[test]
+fn digit_not_on_a_structure() {
+    let e = error("PP1\nPP.\n");
+    assert!(e.contains("line 1, column 3: 1 isn't on top"), "{e}");
+}
+
+#[test]
+fn structure_that_doesnt_fit() {
+    let e = error("..\n.PP\n");
+    assert!(e.contains("line 2, column 2: AirPump doesn't fit"), "{e}");
+}
+
+#[test]
+fn more_than_one_digit() {
+    let e = error("P1\n2P\n");
+    assert!(
+        e.contains("line 1, column 1: AirPump has more than one digit"),
+        "{e}"
+    );
+}
+
+#[test]
+fn reused_name() {
+    let e = error("P1\nPP\nP1\nPP\n");
+    assert!(
+        e.contains("line 3, column 1: 1 is used more than once"),
+        "{e}"
+    );
+}
+
+#[test]
+fn errors_count_comment_lines() {
+    let e = error("# a pump\n# and something else\nPP\nP.\n");
+    assert!(
+        e.contains("line 3, column 1: AirPump is 2x2, but line 4, column 2"),
+        "{e}"
+    );
+}
@@ -0,0 +1,100 @@
+//! the blueprint file format should come back the way it was written, names and all,
+//! so files in a library stay diffable after going through `BlueprintLibrary::save_dir`.
+
+use std::{fs, path::PathBuf};
+
+use immortality_factory_laboratory::{
+    library::{BlueprintLibrary, NamedBlueprint},
+    prelude::*,
+};
+
+const SOURCE: &str = "\
+size (12, 8)
+
+pump = AirPump @ (0, 0)
+ref = Refinery @ (2, 0)
+ref.out.0 = GloomShard
+ref.storage.3 = GloomShard
+split = Splitter @ (8, 0)
+door = AbysalDoor @ (8, 4)
+
+pump.0 -> ref.0
+ref.0 -> split.0
+split.1 -> door.0
+
+output shards = split.0
+";
+
+fn write(blueprint: &NamedBlueprint) -> String {
+    let mut out = vec![];
+    blueprint.write(&mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn parse_write_parse() {
+    let first = NamedBlueprint::parse(SOURCE).unwrap();
+    let written = write(&first);
+    assert_eq!(written, SOURCE);
+    let second = NamedBlueprint::parse(&written).unwrap();
+    assert_eq!(second.contents.structures, first.contents.structures);
+    assert_eq!(second.size, first.size);
+    assert_eq!(second.outputs, first.outputs);
+    assert_eq!(
+        second.names.values().collect::<Vec<_>>(),
+        first.names.values().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn new_structures_dont_rename_old_ones() {
+    let mut blueprint = NamedBlueprint::parse(SOURCE).unwrap();
+    blueprint.blueprint.contents.place(Laboratory, 0, 10);
+    let written = write(&blueprint);
+    assert!(written.contains("\npump = AirPump @ (0, 0)\n"), "{written}");
+    assert!(
+        written.contains("\ndoor = AbysalDoor @ (8, 4)\n"),
+        "{written}"
+    );
+    assert!(
+        written.contains("\nlaboratory_4 = Laboratory @ (0, 10)\n"),
+        "{written}"
+    );
+    let reparsed = NamedBlueprint::parse(&written).unwrap();
+    assert_eq!(reparsed.contents.structures, blueprint.contents.structures);
+}
+
+/// a directory of its own under the system temp dir, emptied out first.
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ifl-{}-{name}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn library_keeps_dotted_names() {
+    let dir = scratch_dir("dotted");
+    let mut library = BlueprintLibrary::new();
+    for name in ["line", "line.v2", "a.1", "a.2"] {
+        library.insert(name, NamedBlueprint::parse(SOURCE).unwrap());
+    }
+    library.save_dir(&dir).unwrap();
+    let loaded = BlueprintLibrary::load_dir(&dir).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(
+        loaded.blueprints.keys().collect::<Vec<_>>(),
+        ["a.1", "a.2", "line", "line.v2"]
+    );
+    assert_eq!(write(loaded.get("line.v2").unwrap()), SOURCE);
+}
+
+#[test]
+fn library_refuses_names_with_slashes() {
+    let dir = scratch_dir("slashes");
+    let mut library = BlueprintLibrary::new();
+    library.insert("ok", NamedBlueprint::parse(SOURCE).unwrap());
+    library.insert("lines/one", NamedBlueprint::parse(SOURCE).unwrap());
+    let error = library.save_dir(&dir).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(!dir.exists());
+}
@@ -0,0 +1,179 @@
+//! structures that come with items already in them.
+
+use immortality_factory_laboratory::{
+    builder::{self, StorageOverflow},
+    prelude::*,
+    structure::StructureDataFull,
+};
+
+fn storage_len(kind: StructureKind) -> usize {
+    StructureDataFull::from(kind).get_storage().len()
+}
+
+#[test]
+fn fill_storage_to_the_brim() {
+    let capacity = storage_len(StorageVault);
+    assert!(capacity > 0);
+    let vault =
+        StructureDataFull::from(builder::StorageVault::filled(GloomShard, capacity).unwrap());
+    assert!(vault.get_storage().iter().all(|&item| item == GloomShard));
+
+    let refinery = builder::Refinery::new()
+        .with_storage(vec![RichAir; storage_len(Refinery)])
+        .unwrap();
+    let refinery = StructureDataFull::from(refinery);
+    assert!(refinery.get_storage().iter().all(|&item| item == RichAir));
+}
+
+#[test]
+fn one_past_capacity_overflows() {
+    let capacity = storage_len(StorageVault);
+    assert_eq!(
+        builder::StorageVault::filled(GloomShard, capacity + 1),
+        Err(StorageOverflow {
+            kind: StorageVault,
+            capacity,
+        })
+    );
+    let capacity = storage_len(Refinery);
+    let error = builder::Refinery::new()
+        .with_storage(vec![RichAir; capacity + 1])
+        .unwrap_err();
+    assert_eq!(error.capacity, capacity);
+    assert_eq!(
+        error.to_string(),
+        format!("Refinery only has room for {capacity} items, tried to put in more")
+    );
+}
+
+#[test]
+fn endless_storage_overflows_without_hanging() {
+    let error = builder::StorageVault::new()
+        .with_storage(std::iter::repeat(GloomShard))
+        .unwrap_err();
+    assert_eq!(error.capacity, storage_len(StorageVault));
+}
+
+#[test]
+fn partly_filled_storage_keeps_the_rest() {
+    let vault = builder::StorageVault::new()
+        .storage(3, ManaDust)
+        .with_storage([GloomShard, BrightShard])
+        .unwrap();
+    let storage = StructureDataFull::from(vault).get_storage().to_vec();
+    assert_eq!(&storage[..4], [GloomShard, BrightShard, Empty, ManaDust]);
+    assert!(storage[4..].iter().all(|&item| item == Empty));
+}
+
+#[test]
+#[should_panic(expected = "Tried to set Refinery storage #99")]
+fn storage_slot_past_the_end() {
+    builder::Refinery::new().storage(99, GloomShard);
+}
+
+/// places `structure` in a world of its own and hands back what ended up there.
+fn placed(structure: impl Placeable<Id = Structure>) -> StructureDataFull {
+    let mut world = World::new();
+    let handle = world.place(structure, 0, 0);
+    world.get_structure(handle).structure
+}
+
+fn inputs(s: &StructureDataFull) -> Vec<Item> {
+    s.get_inputs().iter().map(|port| port.item).collect()
+}
+
+fn outputs(s: &StructureDataFull) -> Vec<Item> {
+    s.get_outputs().iter().map(|port| port.item).collect()
+}
+
+#[test]
+fn output_only() {
+    let pump = placed(builder::AirPump::new().output(RichAir));
+    assert_eq!(pump.kind(), AirPump);
+    assert!(inputs(&pump).is_empty());
+    assert_eq!(outputs(&pump), [RichAir]);
+}
+
+#[test]
+fn one_input_one_output() {
+    let refinery = placed(builder::Refinery::new().input(RichAir).output(ManaCrystal));
+    assert_eq!(inputs(&refinery), [RichAir]);
+    assert_eq!(outputs(&refinery), [ManaCrystal]);
+}
+
+#[test]
+fn many_inputs_one_output() {
+    let unifier = placed(
+        builder::Unifier::new()
+            .input(0, GloomShard)
+            .input(2, BrightShard)
+            .output(EqualizedOrb),
+    );
+    assert_eq!(inputs(&unifier), [GloomShard, Empty, BrightShard]);
+    assert_eq!(outputs(&unifier), [EqualizedOrb]);
+}
+
+#[test]
+fn one_input_many_outputs() {
+    let disharmonizer = placed(
+        builder::Disharmonizer::new()
+            .input(ManaCrystal)
+            .output(1, ManaDust)
+            .output(3, SilicaPowder),
+    );
+    assert_eq!(inputs(&disharmonizer), [ManaCrystal]);
+    let outputs = outputs(&disharmonizer);
+    assert_eq!(outputs[1], ManaDust);
+    assert_eq!(outputs[3], SilicaPowder);
+    assert_eq!(outputs.iter().filter(|&&item| item != Empty).count(), 2);
+}
+
+#[test]
+fn input_only() {
+    let door = placed(builder::AbysalDoor::new().input(LiquidCurse));
+    assert_eq!(inputs(&door), [LiquidCurse]);
+    assert!(outputs(&door).iter().all(|&item| item == Empty));
+}
+
+#[test]
+fn many_inputs_no_output() {
+    let infuser = placed(builder::RitualInfuser::new().input(1, LifeForce));
+    assert_eq!(inputs(&infuser)[1], LifeForce);
+    assert_eq!(
+        inputs(&infuser)
+            .iter()
+            .filter(|&&item| item != Empty)
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn inputs_outputs_and_storage() {
+    let vault = placed(
+        builder::StorageVault::new()
+            .input(GloomShard)
+            .output(GloomShard)
+            .storage(0, GloomShard),
+    );
+    assert_eq!(inputs(&vault), [GloomShard]);
+    assert_eq!(outputs(&vault), [GloomShard]);
+    assert_eq!(vault.get_storage()[0], GloomShard);
+}
+
+#[test]
+fn empty_builder_is_an_empty_structure() {
+    for (built, kind) in [
+        (placed(builder::BigSplitter::new()), BigSplitter),
+        (placed(builder::BigMerger::default()), BigMerger),
+        (placed(builder::SingleStorage::new()), SingleStorage),
+    ] {
+        assert_eq!(built, StructureDataFull::from(kind));
+    }
+}
+
+#[test]
+#[should_panic(expected = "Tried to set Unifier input #7, does not exist.")]
+fn port_past_the_end() {
+    builder::Unifier::new().input(7, GloomShard);
+}
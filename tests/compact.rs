@@ -0,0 +1,84 @@
+//! squeezing a layout together.
+
+use immortality_factory_laboratory::prelude::*;
+
+/// a few short lines spread out with lots of room between them, plus something lone in the
+/// middle to pin.
+fn sprawl() -> (World, Structure) {
+    let mut world = World::new();
+    for row in 0..3 {
+        let y = 12 * row;
+        let pump = world.place(AirPump, 0, y);
+        let split = world.place(Splitter, 9, y);
+        let top = world.place(Refinery, 20, y);
+        let bottom = world.place(Refinery, 20, y + 5);
+        world.connect_all([
+            (pump.output(0), split.input(0)),
+            (split.output(0), top.input(0)),
+            (split.output(1), bottom.input(0)),
+        ]);
+    }
+    let lab = world.place(Laboratory, 40, 15);
+    (world, lab)
+}
+
+/// whether `a` and `b` are at least `gap` tiles apart.
+fn apart(a: &(Position, Size), b: &(Position, Size), gap: Coord) -> bool {
+    let ((a, sa), (b, sb)) = (a, b);
+    a.x >= b.x + sb.w + gap
+        || b.x >= a.x + sa.w + gap
+        || a.y >= b.y + sb.h + gap
+        || b.y >= a.y + sa.h + gap
+}
+
+fn assert_compacted(gap: Coord) {
+    let (mut world, lab) = sprawl();
+    let before = world.clone();
+    let lab_at = world.get_structure(lab).pos;
+    world.compact(&[lab], gap);
+
+    // pinned stays put
+    assert_eq!(world.get_structure(lab).pos, lab_at);
+    // nothing on top of anything, or closer than the gap
+    let spots: Vec<(Position, Size)> = world
+        .structures
+        .iter()
+        .map(|s| (s.pos, s.structure.size()))
+        .collect();
+    for (i, a) in spots.iter().enumerate() {
+        for b in &spots[i + 1..] {
+            assert!(apart(a, b, gap), "gap {gap}: {a:?} and {b:?} are too close");
+        }
+    }
+    let mut check = World::new();
+    for s in &world.structures {
+        assert!(check.try_place(s.structure, s.pos.x, s.pos.y).is_ok());
+    }
+    // same machines, same wires, less room
+    assert_eq!(world.structures.len(), before.structures.len());
+    for (old, new) in before.structures.iter().zip(&world.structures) {
+        assert_eq!(old.structure, new.structure);
+    }
+    let (_, old_size) = before.bounds().unwrap();
+    let (_, new_size) = world.bounds().unwrap();
+    assert!(new_size.w * new_size.h < old_size.w * old_size.h);
+    assert!(world.wire_stats().total_length < before.wire_stats().total_length);
+}
+
+#[test]
+fn compact_without_gap() {
+    assert_compacted(0);
+}
+
+#[test]
+fn compact_with_gap() {
+    assert_compacted(1);
+    assert_compacted(3);
+}
+
+#[test]
+#[should_panic(expected = "Cannot keep a negative gap, got -1.")]
+fn negative_gap() {
+    let (mut world, _) = sprawl();
+    world.compact(&[], -1);
+}
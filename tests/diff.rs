@@ -0,0 +1,156 @@
+//! what changed between two worlds.
+
+use immortality_factory_laboratory::{
+    builder,
+    prelude::*,
+    world::{Moved, Slot, SlotChange},
+};
+
+/// a pump feeding the top one of two refineries.
+fn before() -> World {
+    let mut world = World::new();
+    let pump = world.place(AirPump, 0, 0);
+    let top = world.place(Refinery, 2, 0);
+    world.place(Refinery, 2, 2);
+    world.connect(pump.output(0), top.input(0));
+    world
+}
+
+#[test]
+fn same_world_no_changes() {
+    let world = before();
+    let diff = world.diff(&before());
+    assert!(diff.is_empty());
+    assert_eq!(diff.to_string(), "no changes\n");
+}
+
+#[test]
+fn added_and_removed() {
+    let old = before();
+    let mut new = World::new();
+    let pump = new.place(AirPump, 0, 0);
+    let top = new.place(Refinery, 2, 0);
+    let lab = new.place(Laboratory, 0, 6);
+    new.connect(pump.output(0), top.input(0));
+    let diff = old.diff(&new);
+    assert_eq!(diff.added, [(lab, Position { x: 0, y: 6 })]);
+    assert_eq!(diff.removed.len(), 1);
+    let (removed, at) = diff.removed[0];
+    assert_eq!((removed.index(), removed.kind()), (2, Refinery));
+    assert_eq!(at, Position { x: 2, y: 2 });
+    assert!(diff.moved.is_empty());
+    assert!(diff.connected.is_empty() && diff.disconnected.is_empty());
+    let text = diff.to_string();
+    assert!(text.contains("- #2 Refinery at (2, 2)"), "{text}");
+    assert!(text.contains("+ #2 Laboratory at (0, 6)"), "{text}");
+}
+
+#[test]
+fn moved_keeps_its_connections() {
+    let old = before();
+    let mut new = World::new();
+    let bottom = new.place(Refinery, 2, 2);
+    let pump = new.place(AirPump, 0, 0);
+    let top = new.place(Refinery, 2, 6);
+    new.connect(pump.output(0), top.input(0));
+    let diff = old.diff(&new);
+    assert_eq!(diff.moved.len(), 1);
+    let Moved {
+        old: was,
+        new: now,
+        from,
+        to,
+    } = diff.moved[0];
+    assert_eq!(was.index(), 1);
+    assert_eq!(now, top);
+    assert_eq!(
+        (from, to),
+        (Position { x: 2, y: 0 }, Position { x: 2, y: 6 })
+    );
+    // the one at (2, 2) didn't go anywhere, even though it comes first now
+    assert!(diff.moved.iter().all(|m| m.new != bottom));
+    assert!(diff.added.is_empty() && diff.removed.is_empty());
+    assert!(diff.connected.is_empty() && diff.disconnected.is_empty());
+    let text = diff.to_string();
+    assert!(
+        text.contains("~ #2 Refinery moved (was #1) from (2, 0) to (2, 6)"),
+        "{text}"
+    );
+}
+
+#[test]
+fn changed_connections() {
+    let old = before();
+    let mut new = World::new();
+    let pump = new.place(AirPump, 0, 0);
+    let top = new.place(Refinery, 2, 0);
+    let bottom = new.place(Refinery, 2, 2);
+    new.connect(pump.output(0), bottom.input(0));
+    let diff = old.diff(&new);
+    assert_eq!(diff.disconnected, [(pump.output(0), top.input(0))]);
+    assert_eq!(diff.connected, [(pump.output(0), bottom.input(0))]);
+    assert!(diff.moved.is_empty());
+    let text = diff.to_string();
+    assert!(text.contains("- #0 out 0 -> #1 in 0"), "{text}");
+    assert!(text.contains("+ #0 out 0 -> #2 in 0"), "{text}");
+}
+
+#[test]
+fn same_kind_swapping_places() {
+    let old = before();
+
+    // the two refineries trade spots, and the wire goes with the one that was on top.
+    // going by spot, that's the pump now feeding the bottom one instead.
+    let mut new = World::new();
+    let pump = new.place(AirPump, 0, 0);
+    let was_top = new.place(Refinery, 2, 2);
+    let was_bottom = new.place(Refinery, 2, 0);
+    new.connect(pump.output(0), was_top.input(0));
+    let diff = old.diff(&new);
+    assert!(diff.moved.is_empty(), "{diff}");
+    assert_eq!(diff.disconnected, [(pump.output(0), was_bottom.input(0))]);
+    assert_eq!(diff.connected, [(pump.output(0), was_top.input(0))]);
+
+    // and if the wire stays on the top spot, only the numbering changed, which isn't a change
+    let mut new = World::new();
+    let pump = new.place(AirPump, 0, 0);
+    new.place(Refinery, 2, 2);
+    let top = new.place(Refinery, 2, 0);
+    new.connect(pump.output(0), top.input(0));
+    assert!(old.diff(&new).is_empty());
+}
+
+#[test]
+fn changed_items() {
+    let old = before();
+    let mut new = World::new();
+    let pump = new.place(builder::AirPump::new().output(RichAir), 0, 0);
+    let top = new.place(Refinery, 2, 0);
+    new.place(builder::Refinery::new().storage(1, GloomShard), 2, 2);
+    new.connect(pump.output(0), top.input(0));
+    let diff = old.diff(&new);
+    assert_eq!(
+        diff.slots,
+        [
+            SlotChange {
+                structure: pump,
+                slot: Slot::Output,
+                index: 0,
+                before: Empty,
+                after: RichAir,
+            },
+            SlotChange {
+                structure: new.structures_of_kind(Refinery).nth(1).unwrap(),
+                slot: Slot::Storage,
+                index: 1,
+                before: Empty,
+                after: GloomShard,
+            },
+        ]
+    );
+    let text = diff.to_string();
+    assert!(
+        text.contains("* #2 Refinery storage 1: Empty -> GloomShard"),
+        "{text}"
+    );
+}
@@ -0,0 +1,238 @@
+//! golden file tests for the save format.
+//!
+//! every file in `tests/golden` is what `World::export` wrote the last time someone looked at
+//! the format on purpose. if you changed it on purpose, rerun with `BLESS=1` to rewrite them,
+//! then check the diff (and that the game still loads them) before committing.
+
+use std::{fs, path::PathBuf};
+
+use immortality_factory_laboratory::{prelude::*, structure::StructureDataFull};
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{name}.sav"))
+}
+
+fn export(world: &World) -> String {
+    let mut out = vec![];
+    world.export(&mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+fn import(save: &str) -> World {
+    World::import(&mut save.as_bytes()).unwrap()
+}
+
+fn assert_golden(name: &str, world: &World) {
+    let path = golden_path(name);
+    let actual = export(world);
+    if std::env::var_os("BLESS").is_some() {
+        fs::write(&path, &actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("could not read {}: {e}", path.display()));
+    // not assert_eq, a few hundred lines of save file makes for an unreadable panic
+    if let Some((line, (a, e))) = actual
+        .lines()
+        .zip(expected.lines())
+        .enumerate()
+        .find(|(_, (a, e))| a != e)
+    {
+        panic!(
+            "{name}: export differs from {} at line {}\n\
+            - expected: {e}\n\
+            - actual:   {a}",
+            path.display(),
+            line + 1
+        );
+    }
+    assert!(actual == expected, "{name}: export differs in length");
+}
+
+/// one of `kind`, with something in every slot the save has room for so items get exported too.
+fn stocked(kind: StructureKind) -> World {
+    let mut structure = StructureDataFull::from(kind);
+    let connectors = kind.connectors();
+    for port in structure
+        .get_inputs_mut()
+        .iter_mut()
+        .take(connectors.inputs.len())
+    {
+        port.item = GloomShard;
+    }
+    for port in structure
+        .get_outputs_mut()
+        .iter_mut()
+        .take(connectors.outputs.len())
+    {
+        port.item = EqualizedOrb;
+    }
+    for (slot, item) in structure.get_storage_mut().iter_mut().enumerate() {
+        *item = Item::ITEMS[slot];
+    }
+    let mut world = World::new();
+    world.place(structure, 3, 2);
+    world
+}
+
+/// a bit of everything that connects.
+fn factory() -> World {
+    let mut world = World::new();
+    let pump = world.place(AirPump, 0, 0);
+    let split = world.place(Splitter, 3, 0);
+    let big_split = world.place(BigSplitter, 5, 0);
+    let refinery = world.place(Refinery, 7, 0);
+    let unifier = world.place(Unifier, 14, 0);
+    let merger = world.place(Merger, 18, 0);
+    let big_merger = world.place(BigMerger, 20, 0);
+    let door = world.place(AbysalDoor, 22, 0);
+    let market = world.place(SubdimensionalMarket, 0, 6);
+    let disharmonizer = world.place(Disharmonizer, 5, 6);
+    let vault = world.place(StorageVault, 10, 6);
+    let infuser = world.place(RitualInfuser, 16, 6);
+    let lab = world.place(Laboratory, 22, 6);
+    world.place(SingleStorage, 0, 12);
+    world.connect_all([
+        (pump.output(0), split.input(0)),
+        (split.output(0), big_split.input(0)),
+        (split.output(1), market.input(0)),
+        (big_split.output(0), refinery.input(0)),
+        (big_split.output(1), unifier.input(0)),
+        (big_split.output(4), unifier.input(2)),
+        (refinery.output(0), merger.input(1)),
+        (unifier.output(0), big_merger.input(3)),
+        (market.output(2), disharmonizer.input(0)),
+        (disharmonizer.output(0), vault.input(0)),
+        (disharmonizer.output(3), infuser.input(1)),
+        (vault.output(0), lab.input(0)),
+        (merger.output(0), big_merger.input(0)),
+        (big_merger.output(0), door.input(0)),
+    ]);
+    world
+}
+
+#[test]
+fn export_each_kind() {
+    for &kind in StructureKind::KINDS {
+        assert_golden(&format!("{kind:?}"), &stocked(kind));
+    }
+}
+
+#[test]
+fn export_factory() {
+    assert_golden("factory", &factory());
+}
+
+#[test]
+fn import_export_round_trip() {
+    let mut names: Vec<String> = StructureKind::KINDS
+        .iter()
+        .map(|kind| format!("{kind:?}"))
+        .collect();
+    names.push("factory".to_owned());
+    for name in names {
+        let path = golden_path(&name);
+        let save = fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("could not read {}: {e}", path.display()));
+        assert!(
+            export(&import(&save)) == save,
+            "{name}: round trip changed the save"
+        );
+    }
+}
+
+#[test]
+fn save_round_trip() {
+    let save = fs::read_to_string(golden_path("factory")).unwrap();
+    let mut out = vec![];
+    Save::import(&mut save.as_bytes())
+        .unwrap()
+        .export(&mut out)
+        .unwrap();
+    assert!(
+        out == save.as_bytes(),
+        "round trip through Save changed the save"
+    );
+}
+
+#[test]
+fn export_import_round_trip() {
+    for world in StructureKind::KINDS.iter().map(|&kind| stocked(kind)) {
+        assert_eq!(import(&export(&world)).structures, world.structures);
+    }
+    let world = factory();
+    assert_eq!(import(&export(&world)).structures, world.structures);
+}
+
+/// `world`'s save file, with the machines numbered `ids` instead of 0, 1, 2...
+/// like a save where the game has had things built and torn down.
+fn with_gaps(world: &World, ids: &[usize]) -> String {
+    let mut section = "";
+    let mut out = String::new();
+    for line in export(world).lines() {
+        if line.starts_with('[') {
+            section = line;
+        }
+        let mut line = line.to_owned();
+        if section == "[Machines]" {
+            for (index, id) in ids.iter().enumerate().rev() {
+                if let Some(rest) = line.strip_prefix(&format!("{index}-")) {
+                    line = format!("{id}-{rest}");
+                }
+                line = line.replace(
+                    &format!("+connected_machine+:{}", 100000 + index),
+                    &format!("+connected_machine+:{}", 100000 + id),
+                );
+            }
+        }
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
+
+#[test]
+fn paste_into_save_checks_collisions_and_renumbers() {
+    let mut line = World::new();
+    let pump = line.place(AirPump, 0, 0);
+    let refinery = line.place(Refinery, 2, 0);
+    line.connect(pump.output(0), refinery.input(0));
+
+    let mut save = Save::import(&mut with_gaps(&line, &[3, 7]).as_bytes()).unwrap();
+    assert_eq!(save.world.structures, line.structures);
+
+    // right on top of what's there
+    assert!(save.paste(&line, 1, 0).is_err());
+    assert_eq!(save.world.structures.len(), 2);
+
+    let pasted = save.paste(&line, 0, 4).unwrap();
+    assert_eq!(pasted.get(pump).index(), 2);
+    assert_eq!(pasted.get(refinery).index(), 3);
+
+    let mut out = vec![];
+    save.export(&mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    let machines = out
+        .split("[Machines]\n")
+        .nth(1)
+        .unwrap()
+        .split("\n[")
+        .next()
+        .unwrap();
+    let mut ids: Vec<usize> = machines
+        .lines()
+        .filter_map(|line| line.split_once('-')?.0.parse().ok())
+        .collect();
+    ids.dedup();
+    assert_eq!(ids, [0, 1, 2, 3]);
+    assert!(machines.contains("total=\"4.000000\""), "{machines}");
+    // each pump points at the refinery right after it
+    let connections: Vec<&str> = machines
+        .match_indices("+connected_machine+:")
+        .filter_map(|(i, key)| machines[i + key.len()..].split(',').next())
+        .filter(|id| !id.starts_with('-'))
+        .collect();
+    assert_eq!(connections, ["100001", "100000", "100003", "100002"]);
+}
@@ -0,0 +1,100 @@
+//! wiring one output to many inputs and back, through trees placed wherever there's room.
+
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+use immortality_factory_laboratory::{
+    generators::{merger_tree, splitter_tree},
+    prelude::*,
+};
+
+/// `count` refineries in a column, well away from everything.
+fn refineries(world: &mut World, count: usize) -> Vec<Structure> {
+    (0..count)
+        .map(|i| world.place(Refinery, 20, 3 * i as Coord))
+        .collect()
+}
+
+#[test]
+fn fan_out() {
+    let mut world = World::new();
+    let pump = world.place(AirPump, 0, 0);
+    let refineries = refineries(&mut world, 7);
+    let inputs: Vec<PortIn> = refineries.iter().map(|r| r.input(0)).collect();
+    let tree = world.connect_many(pump.output(0), &inputs).unwrap();
+    // one wire in, one per refinery out, and the ones inside the tree
+    let inside = splitter_tree(7).contents.connection_count();
+    assert_eq!(world.connection_count(), 1 + 7 + inside);
+    assert_eq!(world.output_target(pump.output(0)), Some(tree.input(0)));
+    for (i, refinery) in refineries.iter().enumerate() {
+        assert_eq!(world.input_source(refinery.input(0)), Some(tree.output(i)));
+    }
+}
+
+#[test]
+fn fan_in() {
+    let mut world = World::new();
+    let pumps: Vec<Structure> = (0..5).map(|i| world.place(AirPump, 0, 3 * i)).collect();
+    let market = world.place(SubdimensionalMarket, 30, 0);
+    let outputs: Vec<PortOut> = pumps.iter().map(|p| p.output(0)).collect();
+    let tree = world.merge_into(&outputs, market.input(0)).unwrap();
+    let inside = merger_tree(5).contents.connection_count();
+    assert_eq!(world.connection_count(), 5 + 1 + inside);
+    assert_eq!(world.input_source(market.input(0)), Some(tree.output(0)));
+    for (i, pump) in pumps.iter().enumerate() {
+        assert_eq!(world.output_target(pump.output(0)), Some(tree.input(i)));
+    }
+}
+
+#[test]
+fn just_one_is_a_plain_wire() {
+    let mut world = World::new();
+    let pump = world.place(AirPump, 0, 0);
+    let refinery = world.place(Refinery, 3, 0);
+    assert!(
+        world
+            .connect_many(pump.output(0), &[refinery.input(0)])
+            .is_none()
+    );
+    assert_eq!(world.structures.len(), 2);
+    assert_eq!(world.connection_count(), 1);
+}
+
+/// runs `edit`, which should panic with `message` without touching the world.
+fn assert_refused(world: &mut World, message: &str, edit: impl FnOnce(&mut World)) {
+    let before = world.clone();
+    let error = catch_unwind(AssertUnwindSafe(|| edit(world))).unwrap_err();
+    let error = error.downcast_ref::<&str>().copied().unwrap_or_default();
+    assert_eq!(error, message);
+    assert_eq!(*world, before);
+}
+
+#[test]
+fn the_same_port_twice_places_nothing() {
+    let mut world = World::new();
+    let pump = world.place(AirPump, 0, 0);
+    let refinery = world.place(Refinery, 3, 0);
+    assert_refused(&mut world, "Tried to connect the same input twice.", |w| {
+        w.connect_many(pump.output(0), &[refinery.input(0), refinery.input(0)]);
+    });
+    assert_refused(&mut world, "Tried to connect the same output twice.", |w| {
+        w.merge_into(&[pump.output(0), pump.output(0)], refinery.input(0));
+    });
+}
+
+#[test]
+fn connected_ports_place_nothing() {
+    let mut world = World::new();
+    let pump = world.place(AirPump, 0, 0);
+    let other = world.place(AirPump, 0, 3);
+    let refineries = refineries(&mut world, 2);
+    world.connect(other.output(0), refineries[1].input(0));
+    assert_refused(&mut world, "Structure is already connected.", |w| {
+        w.connect_many(
+            pump.output(0),
+            &[refineries[0].input(0), refineries[1].input(0)],
+        );
+    });
+    assert_refused(&mut world, "Structure is already connected.", |w| {
+        w.merge_into(&[pump.output(0), other.output(0)], refineries[0].input(0));
+    });
+}
@@ -0,0 +1,151 @@
+//! the splitter and merger trees, for every size someone might reasonably ask for.
+
+use immortality_factory_laboratory::{generators::*, prelude::*};
+
+const SIZES: std::ops::RangeInclusive<usize> = 2..=40;
+
+/// everything inside the blueprint's size, with nothing on top of anything else.
+fn assert_fits(blueprint: &Blueprint, what: &str) {
+    let mut world = World::new();
+    for s in &blueprint.contents.structures {
+        let size = s.structure.size();
+        assert!(
+            s.pos.x >= 0
+                && s.pos.y >= 0
+                && s.pos.x + size.w <= blueprint.size.w
+                && s.pos.y + size.h <= blueprint.size.h,
+            "{what}: {:?} at {:?} sticks out of {:?}",
+            s.structure.kind(),
+            s.pos,
+            blueprint.size
+        );
+        assert!(
+            world.try_place(s.structure, s.pos.x, s.pos.y).is_ok(),
+            "{what}: overlap at {:?}",
+            s.pos
+        );
+    }
+}
+
+/// the ends are all different, and left for whoever places the tree to hook up.
+fn assert_loose_ends(blueprint: &Blueprint, what: &str) {
+    let contents = &blueprint.contents;
+    for (i, &port) in blueprint.inputs.iter().enumerate() {
+        assert!(
+            !blueprint.inputs[i + 1..].contains(&port),
+            "{what}: input {i} twice"
+        );
+        assert_eq!(contents.input_source(port), None, "{what}: input {i}");
+    }
+    for (i, &port) in blueprint.outputs.iter().enumerate() {
+        assert!(
+            !blueprint.outputs[i + 1..].contains(&port),
+            "{what}: output {i} twice"
+        );
+        assert_eq!(contents.output_target(port), None, "{what}: output {i}");
+    }
+}
+
+#[test]
+fn splitter_trees() {
+    for n in SIZES {
+        let what = format!("splitter_tree({n})");
+        let tree = splitter_tree(n);
+        assert_eq!(tree.inputs.len(), 1, "{what}");
+        assert_eq!(tree.outputs.len(), n, "{what}");
+        assert_eq!(
+            tree.contents.structures.len(),
+            (n - 1).div_ceil(4),
+            "{what}"
+        );
+        assert_fits(&tree, &what);
+        assert_loose_ends(&tree, &what);
+    }
+}
+
+#[test]
+fn merger_trees() {
+    for n in SIZES {
+        let what = format!("merger_tree({n})");
+        let tree = merger_tree(n);
+        assert_eq!(tree.inputs.len(), n, "{what}");
+        assert_eq!(tree.outputs.len(), 1, "{what}");
+        assert_eq!(
+            tree.contents.structures.len(),
+            (n - 1).div_ceil(4),
+            "{what}"
+        );
+        assert_fits(&tree, &what);
+        assert_loose_ends(&tree, &what);
+    }
+}
+
+#[test]
+fn balanced_splitter_trees() {
+    for n in SIZES {
+        let what = format!("balanced_splitter_tree({n})");
+        let tree = balanced_splitter_tree(n);
+        assert_eq!(tree.inputs.len(), 1, "{what}");
+        assert_eq!(tree.outputs.len(), n, "{what}");
+        // a complete tree, minus the branches that only lead past the last end
+        let levels = n.next_power_of_two().trailing_zeros();
+        let splitters: usize = (1..=levels).map(|level| n.div_ceil(1 << level)).sum();
+        assert_eq!(
+            tree.contents.structures_of_kind(Splitter).count(),
+            splitters,
+            "{what}"
+        );
+        assert_fits(&tree, &what);
+        assert_loose_ends(&tree, &what);
+    }
+}
+
+#[test]
+fn balanced_merger_trees() {
+    for n in SIZES {
+        let what = format!("balanced_merger_tree({n})");
+        let tree = balanced_merger_tree(n);
+        assert_eq!(tree.inputs.len(), n, "{what}");
+        assert_eq!(tree.outputs.len(), 1, "{what}");
+        assert_eq!(tree.contents.structures.len(), n - 1, "{what}");
+        assert_fits(&tree, &what);
+        assert_loose_ends(&tree, &what);
+    }
+}
+
+#[test]
+#[should_panic(expected = "A tree needs at least 2 ends, got 1.")]
+fn trees_need_two_ends() {
+    balanced_splitter_tree(1);
+}
+
+/// one pump, `tree` split over `n` refineries: how many crafts each refinery gets done.
+fn refinery_crafts(tree: &Blueprint, n: usize) -> Vec<u64> {
+    let mut world = World::new();
+    let pump = world.place(AirPump, 0, 0);
+    let pasted = world.place(tree, 3, 0);
+    world.connect(pump.output(0), pasted.input(0));
+    let x = 4 + tree.size.w;
+    let refineries: Vec<Structure> = (0..n)
+        .map(|i| world.place(Refinery, x, 2 * i as Coord))
+        .collect();
+    for (i, refinery) in refineries.iter().enumerate() {
+        world.connect(pasted.output(i), refinery.input(0));
+    }
+    let report = world.flow_report(600.0);
+    refineries
+        .iter()
+        .map(|r| report.machines[r.index()].crafts)
+        .collect()
+}
+
+#[test]
+fn balanced_splitter_tree_shares_evenly_in_the_sim() {
+    // small splitters taking turns is only how the sim sees it, see the docs
+    let crafts = refinery_crafts(&balanced_splitter_tree(13), 13);
+    let (min, max) = (crafts.iter().min().unwrap(), crafts.iter().max().unwrap());
+    assert!(*min > 0 && max - min <= 1, "{crafts:?}");
+    // where going lowest first leaves most of them with nothing
+    let crafts = refinery_crafts(&splitter_tree(13), 13);
+    assert!(crafts.iter().filter(|&&c| c == 0).count() > 6, "{crafts:?}");
+}
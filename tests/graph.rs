@@ -0,0 +1,131 @@
+//! loops, orderings and groups of connected structures.
+
+use immortality_factory_laboratory::prelude::*;
+
+/// a pump split over two refineries that merge back together into a market.
+/// returns the structures in the order they were placed.
+fn diamond(world: &mut World, y: Coord) -> [Structure; 6] {
+    let pump = world.place(AirPump, 0, y);
+    let split = world.place(Splitter, 3, y);
+    let top = world.place(Refinery, 5, y);
+    let bottom = world.place(Refinery, 5, y + 2);
+    let merge = world.place(Merger, 12, y);
+    let market = world.place(SubdimensionalMarket, 14, y);
+    world.connect_all([
+        (pump.output(0), split.input(0)),
+        (split.output(0), top.input(0)),
+        (split.output(1), bottom.input(0)),
+        (top.output(0), merge.input(0)),
+        (bottom.output(0), merge.input(1)),
+        (merge.output(0), market.input(0)),
+    ]);
+    [pump, split, top, bottom, merge, market]
+}
+
+/// a pump into a merger, through a refinery, then a splitter that sends half back
+/// into the merger and half on to a market.
+fn with_loop(world: &mut World, y: Coord) -> [Structure; 5] {
+    let pump = world.place(AirPump, 0, y);
+    let merge = world.place(Merger, 3, y);
+    let refinery = world.place(Refinery, 5, y);
+    let split = world.place(Splitter, 12, y);
+    let market = world.place(SubdimensionalMarket, 14, y);
+    world.connect_all([
+        (pump.output(0), merge.input(0)),
+        (merge.output(0), refinery.input(0)),
+        (refinery.output(0), split.input(0)),
+        (split.output(0), merge.input(1)),
+        (split.output(1), market.input(0)),
+    ]);
+    [pump, merge, refinery, split, market]
+}
+
+#[test]
+fn diamond_has_no_loops() {
+    let mut world = World::new();
+    let [pump, split, top, bottom, merge, market] = diamond(&mut world, 0);
+    assert!(world.cycles().is_empty());
+    let components = world.strongly_connected_components();
+    assert_eq!(components.len(), 6);
+    assert!(components.iter().all(|c| c.len() == 1));
+    // downstream first
+    assert_eq!(components[0], [market]);
+    assert_eq!(components[5], [pump]);
+
+    let order = world.topological_order().unwrap();
+    let at = |s: Structure| order.iter().position(|&o| o == s).unwrap();
+    assert!(at(pump) < at(split));
+    assert!(at(split) < at(top) && at(split) < at(bottom));
+    assert!(at(top) < at(merge) && at(bottom) < at(merge));
+    assert!(at(merge) < at(market));
+
+    assert_eq!(world.depths(), Some(vec![0, 1, 2, 2, 3, 4]));
+    assert_eq!(world.longest_path(), Some(4));
+    assert_eq!(world.weakly_connected_components().len(), 1);
+}
+
+#[test]
+fn loop_is_found() {
+    let mut world = World::new();
+    let [pump, merge, refinery, split, market] = with_loop(&mut world, 0);
+    assert_eq!(world.cycles(), [vec![merge, refinery, split]]);
+    let components = world.strongly_connected_components();
+    assert_eq!(
+        components,
+        [vec![market], vec![merge, refinery, split], vec![pump]]
+    );
+    assert_eq!(world.topological_order(), None);
+    assert_eq!(world.depths(), None);
+    assert_eq!(world.longest_path(), None);
+}
+
+#[test]
+fn loop_through_itself() {
+    let mut world = World::new();
+    let pump = world.place(AirPump, 0, 0);
+    let merge = world.place(Merger, 3, 0);
+    world.connect(pump.output(0), merge.input(0));
+    world.connect(merge.output(0), merge.input(1));
+    assert_eq!(world.cycles(), [vec![merge]]);
+    assert_eq!(world.topological_order(), None);
+}
+
+#[test]
+fn disconnected_parts() {
+    let mut world = World::new();
+    let split_up = diamond(&mut world, 0);
+    let lone = world.place(Laboratory, 0, 6);
+    let looped = with_loop(&mut world, 10);
+
+    let groups = world.weakly_connected_components();
+    assert_eq!(groups, [split_up.to_vec(), vec![lone], looped.to_vec()]);
+    // only the loop is a loop
+    assert_eq!(world.cycles(), [looped[1..4].to_vec()]);
+    assert_eq!(world.topological_order(), None);
+
+    // without the loop there's an order again, across every part
+    let mut world = World::new();
+    let split_up = diamond(&mut world, 0);
+    let lone = world.place(Laboratory, 0, 6);
+    let pump = world.place(AirPump, 0, 10);
+    let refinery = world.place(Refinery, 3, 10);
+    world.connect(pump.output(0), refinery.input(0));
+    let order = world.topological_order().unwrap();
+    assert_eq!(order.len(), world.structures.len());
+    let at = |s: Structure| order.iter().position(|&o| o == s).unwrap();
+    assert!(at(pump) < at(refinery));
+    assert!(at(split_up[0]) < at(split_up[5]));
+    let depths = world.depths().unwrap();
+    assert_eq!(depths, [0, 1, 2, 2, 3, 4, 0, 0, 1]);
+    assert_eq!(depths[lone.index()], 0);
+    assert_eq!(world.longest_path(), Some(4));
+}
+
+#[test]
+fn empty_world() {
+    let world = World::new();
+    assert!(world.strongly_connected_components().is_empty());
+    assert!(world.weakly_connected_components().is_empty());
+    assert_eq!(world.topological_order(), Some(vec![]));
+    assert_eq!(world.longest_path(), Some(0));
+}
@@ -0,0 +1,169 @@
+//! undo, redo and transactions, and the placing that doesn't panic.
+
+use immortality_factory_laboratory::prelude::*;
+
+fn line() -> World {
+    let mut world = World::new();
+    let pump = world.place(AirPump, 0, 0);
+    let refinery = world.place(Refinery, 2, 0);
+    world.connect(pump.output(0), refinery.input(0));
+    world
+}
+
+#[test]
+fn undo_and_redo() {
+    let mut world = World::new();
+    let pump = world.place(AirPump, 0, 0);
+    let refinery = world.place(Refinery, 2, 0);
+    world.connect(pump.output(0), refinery.input(0));
+    let done = world.clone();
+
+    assert!(world.undo());
+    assert_eq!(world.output_target(pump.output(0)), None);
+    assert_eq!(world.structures.len(), 2);
+    assert!(world.undo());
+    assert!(world.undo());
+    assert!(world.structures.is_empty());
+    assert!(!world.undo());
+    assert!(!world.can_undo());
+
+    while world.redo() {}
+    assert_eq!(world, done);
+    assert_eq!(world.output_target(pump.output(0)), Some(refinery.input(0)));
+}
+
+#[test]
+fn new_edits_clear_redo() {
+    let mut world = World::new();
+    world.place(AirPump, 0, 0);
+    world.undo();
+    assert!(world.can_redo());
+    world.place(Refinery, 2, 0);
+    assert!(!world.can_redo());
+    assert!(!world.redo());
+}
+
+#[test]
+fn paste_undoes_in_one_go() {
+    let mut world = World::new();
+    world.place(&line(), 0, 0);
+    assert_eq!(world.structures.len(), 2);
+    assert!(world.undo());
+    assert!(world.structures.is_empty());
+}
+
+#[test]
+fn failed_transaction_rolls_back() {
+    let mut world = World::new();
+    world.place(Refinery, 3, 0);
+    let before = world.clone();
+    let result = world.transaction(|w| {
+        let pump = w.try_place(AirPump, 0, 4)?;
+        let refinery = w.try_place(Refinery, 2, 4)?;
+        w.connect(pump.output(0), refinery.input(0));
+        w.paste(&line(), 0, 0)?; // lands on the first refinery
+        Ok::<_, Collision>(())
+    });
+    assert!(result.is_err());
+    assert_eq!(world, before);
+    // only the refinery from before is left to undo
+    assert!(world.undo());
+    assert!(world.structures.is_empty());
+    assert!(!world.can_undo());
+}
+
+#[test]
+fn transaction_undoes_in_one_go() {
+    let mut world = World::new();
+    world.place(Refinery, 0, 10);
+    let result = world.transaction(|w| {
+        w.paste(&line(), 0, 0)?;
+        w.paste(&line(), 0, 4)?;
+        Ok::<_, Collision>(())
+    });
+    assert!(result.is_ok());
+    assert_eq!(world.structures.len(), 5);
+    assert!(world.undo());
+    assert_eq!(world.structures.len(), 1);
+    assert!(world.redo());
+    assert_eq!(world.structures.len(), 5);
+    assert_eq!(world.connection_count(), 2);
+}
+
+#[test]
+fn mirrored_paste_checks_where_things_land() {
+    let line = line();
+    let mut world = World::new();
+    // flipped, the refinery lands where the pump would have been
+    world.place(AirPump, 6, 0);
+    world.place(Refinery, 0, 0);
+    assert!(world.check_paste(&line, 0, 0).is_err());
+    assert!(world.paste_mirrored(line.mirrored_x(), 0, 0).is_err());
+    assert_eq!(world.structures.len(), 2);
+
+    let mut world = World::new();
+    world.place(AirPump, 0, 2);
+    let pasted = world.paste_mirrored(line.mirrored_y(), 0, 0);
+    assert!(pasted.is_ok());
+    assert_eq!(world.structures.len(), 3);
+
+    let blueprint = Blueprint {
+        contents: line,
+        // room to the right, so flipped it moves clear of the refinery
+        size: Size { w: 16, h: 2 },
+        inputs: vec![],
+        outputs: vec![],
+    };
+    let mut world = World::new();
+    world.place(Refinery, 0, 0);
+    assert!(world.paste_blueprint(&blueprint, 0, 0).is_err());
+    assert!(
+        world
+            .paste_blueprint_mirrored(blueprint.mirrored_x(), 0, 0)
+            .is_ok()
+    );
+}
+
+#[test]
+#[should_panic(expected = "Cannot undo in the middle of a transaction.")]
+fn no_undo_inside_a_transaction() {
+    let mut world = line();
+    let _ = world.transaction(|w| {
+        w.place(Laboratory, 0, 4);
+        w.undo();
+        Ok::<_, Collision>(())
+    });
+}
+
+#[test]
+#[should_panic(expected = "Cannot redo in the middle of a transaction.")]
+fn no_redo_inside_a_transaction() {
+    let mut world = line();
+    world.undo();
+    let _ = world.transaction(|w| {
+        w.redo();
+        Ok::<_, Collision>(())
+    });
+}
+
+#[test]
+fn nested_transactions_undo_in_one_go() {
+    let mut world = World::new();
+    let result = world.transaction(|w| {
+        w.place(&line(), 0, 0);
+        // the inner one fails and only takes back its own part
+        let inner = w.transaction(|w| {
+            w.place(Laboratory, 0, 4);
+            w.paste(&line(), 0, 0)
+        });
+        assert!(inner.is_err());
+        assert_eq!(w.structures.len(), 2);
+        w.place(Laboratory, 0, 4);
+        Ok::<_, Collision>(())
+    });
+    assert!(result.is_ok());
+    assert_eq!(world.structures.len(), 3);
+    assert!(world.undo());
+    assert!(world.structures.is_empty());
+    assert!(!world.can_undo());
+}
@@ -0,0 +1,72 @@
+//! what markets make, going by a price table the test fills in itself.
+
+use immortality_factory_laboratory::{
+    builder,
+    market::{Coins, PriceTable},
+    prelude::*,
+};
+
+fn prices() -> PriceTable {
+    let mut prices = PriceTable::new();
+    prices.set(GloomShard, Coins::copper(2));
+    prices.set(EqualizedOrb, Coins::new(0, 1, 1));
+    prices
+}
+
+#[test]
+fn priced_markets_add_up() {
+    let rate = SubdimensionalMarket.machine_speed();
+    let mut world = World::new();
+    world.place(builder::SubdimensionalMarket::new().input(GloomShard), 0, 0);
+    world.place(
+        builder::SubdimensionalMarket::new().input(EqualizedOrb),
+        0,
+        6,
+    );
+    let report = world.market_income(&prices());
+    assert_eq!(report.markets.len(), 2);
+    assert_eq!(report.unpriced, 0);
+    assert_eq!(report.markets[0].item, GloomShard);
+    assert_eq!(report.markets[0].value, Some(Coins::copper(2)));
+    assert_eq!(report.markets[0].income, Coins::copper(2).per_minute(rate));
+    assert_eq!(report.total.copper, 2.0 * rate);
+    assert_eq!(report.total.silver, rate);
+    assert_eq!(report.total.gold, rate);
+}
+
+#[test]
+fn unpriced_markets_are_counted_not_guessed() {
+    let mut world = World::new();
+    world.place(builder::SubdimensionalMarket::new().input(GloomShard), 0, 0);
+    // nothing in the table for this one
+    world.place(
+        builder::SubdimensionalMarket::new().input(AdamantineBar),
+        0,
+        6,
+    );
+    // or for one that doesn't say what it sells
+    world.place(SubdimensionalMarket, 0, 12);
+    let report = world.market_income(&prices());
+    assert_eq!(report.unpriced, 2);
+    assert_eq!(report.markets[1].value, None);
+    assert_eq!(report.markets[2].item, Empty);
+    assert_eq!(
+        report.total,
+        Coins::copper(2).per_minute(SubdimensionalMarket.machine_speed())
+    );
+    let text = report.to_string();
+    assert!(text.contains("#1 sells"), "{text}");
+    assert!(text.ends_with("(2 unpriced)"), "{text}");
+}
+
+#[test]
+fn market_takes_the_item_from_what_feeds_it() {
+    let mut world = World::new();
+    let unifier = world.place(builder::Unifier::new().output(EqualizedOrb), 0, 0);
+    let market = world.place(SubdimensionalMarket, 6, 0);
+    world.connect(unifier.output(0), market.input(0));
+    let report = world.market_income(&prices());
+    assert_eq!(report.markets[0].item, EqualizedOrb);
+    assert_eq!(report.unpriced, 0);
+    assert_eq!(world.market_income(&PriceTable::new()).unpriced, 1);
+}
@@ -0,0 +1,109 @@
+//! pasting lots of copies at once, and hooking them up one after the other.
+
+use immortality_factory_laboratory::prelude::*;
+
+/// one refinery, which takes what the one before it makes.
+fn refinery() -> (World, Structure) {
+    let mut world = World::new();
+    let refinery = world.place(Refinery, 0, 0);
+    (world, refinery)
+}
+
+fn pos(world: &World, structure: Structure) -> (Coord, Coord) {
+    let pos = world.get_structure(structure).pos;
+    (pos.x, pos.y)
+}
+
+#[test]
+fn stack_goes_dx_dy_each_time() {
+    let (blueprint, r) = refinery();
+    let mut world = World::new();
+    let copies = world.stack(&blueprint, 1, 2, 7, 3, 3);
+    let placed: Vec<_> = copies.iter().map(|c| pos(&world, c.get(r))).collect();
+    assert_eq!(placed, [(1, 2), (8, 5), (15, 8)]);
+    assert_eq!(world.connection_count(), 0);
+}
+
+#[test]
+fn grid_rows_and_columns() {
+    let (blueprint, r) = refinery();
+    let mut world = World::new();
+    let grid = world.grid(&blueprint, 0, 10, 6, 2, 3, 2);
+    assert_eq!(grid.len(), 2);
+    assert!(grid.iter().all(|row| row.len() == 3));
+    let placed: Vec<Vec<_>> = grid
+        .iter()
+        .map(|row| row.iter().map(|c| pos(&world, c.get(r))).collect())
+        .collect();
+    assert_eq!(
+        placed,
+        [[(0, 10), (6, 10), (12, 10)], [(0, 12), (6, 12), (12, 12)]]
+    );
+}
+
+#[test]
+fn chain_connects_each_copy_to_the_next() {
+    let (blueprint, r) = refinery();
+    let mut world = World::new();
+    let copies = world.stack(&blueprint, 0, 0, 6, 0, 4);
+    world.chain(&copies, &[(r.output(0), r.input(0))]);
+    assert_eq!(world.connection_count(), 3);
+    for pair in copies.windows(2) {
+        assert_eq!(
+            world.output_target(pair[0].get(r).output(0)),
+            Some(pair[1].get(r).input(0))
+        );
+    }
+    let last = copies.last().unwrap().get(r);
+    assert_eq!(world.output_target(last.output(0)), None);
+    assert_eq!(world.input_source(copies[0].get(r).input(0)), None);
+}
+
+#[test]
+fn stack_chained_hooks_up_like_one_machine() {
+    let (blueprint, r) = refinery();
+    let mut world = World::new();
+    let pump = world.place(AirPump, 0, 0);
+    let chain = world.stack_chained(&blueprint, 2, 0, 0, 2, 3, &[(r.output(0), r.input(0))]);
+    let market = world.place(SubdimensionalMarket, 8, 0);
+    world.connect(pump.output(0), chain.input(0));
+    world.connect(chain.output(0), market.input(0));
+
+    let placed: Vec<_> = chain.copies.iter().map(|c| pos(&world, c.get(r))).collect();
+    assert_eq!(placed, [(2, 0), (2, 2), (2, 4)]);
+    assert_eq!(chain.input(0), chain.first().get(r).input(0));
+    assert_eq!(chain.output(0), chain.last().get(r).output(0));
+    // pump, then each refinery in the order they were stacked, then the market
+    let mut at = pump;
+    let mut order = vec![];
+    while let Some(next) = world.downstream(at).next() {
+        order.push(next);
+        at = next;
+    }
+    let mut expected: Vec<_> = chain.copies.iter().map(|c| c.get(r)).collect();
+    expected.push(market);
+    assert_eq!(order, expected);
+}
+
+#[test]
+fn chain_rows_chains_each_row_on_its_own() {
+    let (blueprint, r) = refinery();
+    let mut world = World::new();
+    let grid = world.grid(&blueprint, 0, 0, 6, 2, 3, 2);
+    world.chain_rows(&grid, &[(r.output(0), r.input(0))]);
+    assert_eq!(world.connection_count(), 4);
+    for row in &grid {
+        assert_eq!(
+            world.output_target(row[0].get(r).output(0)),
+            Some(row[1].get(r).input(0))
+        );
+        assert_eq!(world.output_target(row[2].get(r).output(0)), None);
+    }
+}
+
+#[test]
+#[should_panic(expected = "Cannot chain zero copies.")]
+fn stack_chained_needs_a_copy() {
+    let (blueprint, r) = refinery();
+    World::new().stack_chained(&blueprint, 0, 0, 6, 0, 0, &[(r.output(0), r.input(0))]);
+}
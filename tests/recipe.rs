@@ -0,0 +1,147 @@
+//! filling in slots from recipes and connections.
+//!
+//! the refinery recipe here is made up, it only needs to be something to follow.
+
+use immortality_factory_laboratory::{
+    builder,
+    prelude::*,
+    recipe::{Mismatch, Recipe, RecipeBook},
+};
+
+fn book() -> RecipeBook {
+    let mut book = RecipeBook::new();
+    book.insert(Recipe::new(Refinery, &[RichAir], &[ManaCrystal]));
+    book
+}
+
+fn inputs(world: &World, s: Structure) -> Vec<Item> {
+    let data = &world.get_structure(s).structure;
+    data.get_inputs().iter().map(|port| port.item).collect()
+}
+
+fn outputs(world: &World, s: Structure) -> Vec<Item> {
+    let data = &world.get_structure(s).structure;
+    data.get_outputs().iter().map(|port| port.item).collect()
+}
+
+#[test]
+fn pass_through_and_market_recipes() {
+    let merger = Recipe::pass_through(Merger, GloomShard).unwrap();
+    assert_eq!(merger.inputs, [GloomShard, GloomShard]);
+    assert_eq!(merger.outputs, [GloomShard]);
+    assert_eq!(Recipe::pass_through(Refinery, GloomShard), None);
+    assert_eq!(
+        Recipe::market(GloomShard).outputs,
+        [CopperCoin, SilverCoin, GoldCoin]
+    );
+
+    let book = RecipeBook::new();
+    assert_eq!(
+        book.get(BigSplitter, RichAir),
+        Recipe::pass_through(BigSplitter, RichAir)
+    );
+    assert_eq!(
+        book.get(SubdimensionalMarket, RichAir),
+        Some(Recipe::market(RichAir))
+    );
+    assert_eq!(book.get(Refinery, ManaCrystal), None);
+    assert_eq!(book.only(AirPump), Some(&Recipe::air_pump()));
+}
+
+#[test]
+fn propagate_follows_the_wires() {
+    let mut world = World::new();
+    let pump = world.place(AirPump, 0, 0);
+    let split = world.place(Splitter, 3, 0);
+    let refinery = world.place(Refinery, 5, 0);
+    let merge = world.place(Merger, 12, 0);
+    let market = world.place(SubdimensionalMarket, 14, 0);
+    world.connect_all([
+        (pump.output(0), split.input(0)),
+        (split.output(0), refinery.input(0)),
+        (split.output(1), merge.input(1)),
+        (refinery.output(0), merge.input(0)),
+        (merge.output(0), market.input(0)),
+    ]);
+
+    // pump out, splitter in and both outs, refinery in and out, both merger ins and its out,
+    // market in and its three coins
+    assert_eq!(world.propagate_items(&book()), 13);
+    assert_eq!(outputs(&world, pump), [RichAir]);
+    assert_eq!(outputs(&world, split), [RichAir, RichAir]);
+    assert_eq!(inputs(&world, refinery), [RichAir]);
+    assert_eq!(outputs(&world, refinery), [ManaCrystal]);
+    // the merger goes by its first input that says anything
+    assert_eq!(inputs(&world, merge), [ManaCrystal, RichAir]);
+    assert_eq!(outputs(&world, merge), [ManaCrystal]);
+    assert_eq!(inputs(&world, market), [ManaCrystal]);
+    assert_eq!(outputs(&world, market), [CopperCoin, SilverCoin, GoldCoin]);
+    assert!(world.slot_mismatches().is_empty());
+
+    // nothing left to fill in
+    assert_eq!(world.propagate_items(&book()), 0);
+}
+
+#[test]
+fn propagate_leaves_set_slots_alone() {
+    let mut world = World::new();
+    let pump = world.place(AirPump, 0, 0);
+    let vault = world.place(builder::StorageVault::new().output(GloomShard), 3, 0);
+    let door = world.place(AbysalDoor, 9, 0);
+    world.connect(pump.output(0), vault.input(0));
+    world.connect(vault.output(0), door.input(0));
+    world.propagate_items(&RecipeBook::new());
+    assert_eq!(inputs(&world, vault), [RichAir]);
+    assert_eq!(outputs(&world, vault), [GloomShard]);
+    assert_eq!(inputs(&world, door), [GloomShard]);
+}
+
+#[test]
+fn propagate_without_a_book_only_knows_the_built_in_ones() {
+    let mut world = World::new();
+    let pump = world.place(AirPump, 0, 0);
+    let refinery = world.place(Refinery, 2, 0);
+    let market = world.place(SubdimensionalMarket, 9, 0);
+    world.connect(pump.output(0), refinery.input(0));
+    world.connect(refinery.output(0), market.input(0));
+    world.propagate_items(&RecipeBook::new());
+    assert_eq!(inputs(&world, refinery), [RichAir]);
+    // no telling what the refinery makes, so the market doesn't know what it's selling
+    assert_eq!(outputs(&world, refinery), [Empty]);
+    assert_eq!(inputs(&world, market), [Empty]);
+    assert_eq!(outputs(&world, market), [Empty; 3]);
+}
+
+#[test]
+fn mismatched_slots() {
+    let mut world = World::new();
+    let pump = world.place(AirPump, 0, 0);
+    let refinery = world.place(builder::Refinery::new().input(GloomShard), 2, 0);
+    let door = world.place(AbysalDoor, 9, 0);
+    world.connect(pump.output(0), refinery.input(0));
+    world.connect(refinery.output(0), door.input(0));
+    // nothing configured is nothing wrong
+    assert!(world.slot_mismatches().is_empty());
+
+    world.configure(pump, &Recipe::air_pump());
+    assert_eq!(
+        world.slot_mismatches(),
+        [Mismatch {
+            output: pump.output(0),
+            input: refinery.input(0),
+            sent: RichAir,
+            expected: GloomShard,
+        }]
+    );
+    // and propagating doesn't paper over it
+    world.propagate_items(&RecipeBook::new());
+    assert_eq!(world.slot_mismatches().len(), 1);
+}
+
+#[test]
+#[should_panic(expected = "Tried to use a AirPump recipe on a Refinery.")]
+fn recipe_for_the_wrong_kind() {
+    let mut world = World::new();
+    let refinery = world.place(Refinery, 0, 0);
+    world.configure(refinery, &Recipe::air_pump());
+}
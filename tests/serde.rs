@@ -0,0 +1,71 @@
+//! worlds and blueprints stored as json, the way a blueprint library would keep them.
+#![cfg(feature = "serde")]
+
+use immortality_factory_laboratory::prelude::*;
+
+fn line() -> Blueprint {
+    let mut world = World::new();
+    let pump = world.place(AirPump, 0, 0);
+    let refinery = world.place(Refinery, 2, 0);
+    world.connect(pump.output(0), refinery.input(0));
+    Blueprint {
+        contents: world,
+        size: Size { w: 6, h: 2 },
+        inputs: vec![],
+        outputs: vec![refinery.output(0)],
+    }
+}
+
+fn round_trip(blueprint: &Blueprint) -> Blueprint {
+    serde_json::from_str(&serde_json::to_string(blueprint).unwrap()).unwrap()
+}
+
+#[test]
+fn loaded_worlds_get_fresh_ids() {
+    let original = line();
+    let a = round_trip(&original);
+    let b = round_trip(&original);
+    let host = World::new();
+    let ids = [
+        original.contents.id(),
+        a.contents.id(),
+        b.contents.id(),
+        host.id(),
+    ];
+    for (i, id) in ids.iter().enumerate() {
+        assert!(!ids[i + 1..].contains(id), "{ids:?} has the same id twice");
+    }
+    assert_eq!(a.contents.structures, original.contents.structures);
+}
+
+#[test]
+fn loaded_blueprint_ports_point_at_its_contents() {
+    let blueprint = round_trip(&line());
+    let mut world = World::new();
+    let line = world.place(&blueprint, 0, 0);
+    let market = world.place(SubdimensionalMarket, 8, 0);
+    world.connect(line.output(0), market.input(0));
+    assert_eq!(world.connection_count(), 2);
+}
+
+#[test]
+#[should_panic(expected = "World IDs must match.")]
+fn handles_from_another_loaded_blueprint_are_caught() {
+    let a = round_trip(&line());
+    let b = round_trip(&line());
+    let mut world = World::new();
+    let pasted = world.place(&a.contents, 0, 0);
+    world.place(&b.contents, 0, 4);
+    pasted.get(b.outputs[0]);
+}
+
+#[test]
+fn ports_on_missing_structures_are_rejected() {
+    let mut json: serde_json::Value = serde_json::to_value(line()).unwrap();
+    json["outputs"][0]["structure_id"]["index"] = 5.into();
+    let error = serde_json::from_value::<Blueprint>(json).err().unwrap();
+    assert!(
+        error.to_string().contains("missing structure #5"),
+        "{error}"
+    );
+}
@@ -0,0 +1,133 @@
+//! what the flow simulation says about a few small lines.
+//!
+//! machine speeds are only compared with each other here, so none of this leans on
+//! what unit they're in.
+
+use immortality_factory_laboratory::{
+    prelude::*,
+    sim::{MachineFlow, Simulation},
+};
+
+fn line(world: &mut World, kinds: &[StructureKind]) -> Vec<Structure> {
+    let mut x = 0;
+    let structures: Vec<Structure> = kinds
+        .iter()
+        .map(|&kind| {
+            let structure = world.place(kind, x, 0);
+            x += kind.width() + 1;
+            structure
+        })
+        .collect();
+    for pair in structures.windows(2) {
+        world.connect(pair[0].output(0), pair[1].input(0));
+    }
+    structures
+}
+
+fn assert_adds_up(flow: &MachineFlow) {
+    let total = flow.working + flow.blocked + flow.starved + flow.idle;
+    assert!((total - 1.0).abs() < 1e-9, "{flow:?}");
+}
+
+#[test]
+fn fast_machine_after_a_slow_one_is_starved() {
+    assert!(Refinery.machine_speed() > AirPump.machine_speed());
+    let mut world = World::new();
+    let [pump, refinery] = line(&mut world, &[AirPump, Refinery])[..] else {
+        unreachable!()
+    };
+    let report = world.flow_report(120.0);
+    let (pump, refinery) = (
+        report.machines[pump.index()],
+        report.machines[refinery.index()],
+    );
+    assert_adds_up(&pump);
+    assert_adds_up(&refinery);
+    assert!(pump.working > 0.95, "{report}");
+    assert!(refinery.starved > 0.3, "{report}");
+    assert_eq!(refinery.blocked, 0.0, "{report}");
+    // it keeps up with everything it's given
+    assert!(refinery.crafts.abs_diff(pump.crafts) <= 1, "{report}");
+    assert_eq!(report.bottleneck, Some(pump.structure));
+}
+
+#[test]
+fn slow_machine_at_the_end_blocks_the_rest() {
+    assert!(AbysalDoor.machine_speed() < AirPump.machine_speed());
+    let mut world = World::new();
+    let structures = line(&mut world, &[AirPump, Refinery, AbysalDoor]);
+    let report = world.flow_report(120.0);
+    let [pump, refinery, door] = [0, 1, 2].map(|i| report.machines[structures[i].index()]);
+    assert!(pump.blocked > 0.5, "{report}");
+    assert!(refinery.blocked > 0.5, "{report}");
+    assert!(door.working > 0.95, "{report}");
+    assert_eq!(door.blocked, 0.0, "{report}");
+    assert_eq!(report.bottleneck, Some(door.structure));
+    assert!(report.to_string().ends_with("bottleneck: #2 AbysalDoor"));
+}
+
+#[test]
+fn splitter_shares_the_load() {
+    // the doors are still the slow part, but two get through twice as much as one.
+    // there's more room for things to pile up in, so it takes longer to back up.
+    const SECONDS: f64 = 600.0;
+    let mut world = World::new();
+    let structures = line(&mut world, &[AirPump, Refinery, Splitter]);
+    let splitter = structures[2];
+    let doors = [
+        world.place(AbysalDoor, 20, 0),
+        world.place(AbysalDoor, 20, 2),
+    ];
+    world.connect(splitter.output(0), doors[0].input(0));
+    world.connect(splitter.output(1), doors[1].input(0));
+    let one = {
+        let mut world = World::new();
+        line(&mut world, &[AirPump, Refinery, AbysalDoor]);
+        world.flow_report(SECONDS).machines[2].crafts
+    };
+    let report = world.flow_report(SECONDS);
+    let crafts: Vec<u64> = doors
+        .iter()
+        .map(|d| report.machines[d.index()].crafts)
+        .collect();
+    assert!(
+        crafts.iter().all(|&c| c.abs_diff(one) <= 1),
+        "{crafts:?} vs {one}"
+    );
+    assert!(doors.contains(&report.bottleneck.unwrap()), "{report}");
+    for flow in &report.machines {
+        assert_adds_up(flow);
+    }
+}
+
+#[test]
+fn nothing_hooked_up_is_idle() {
+    let mut world = World::new();
+    let refinery = world.place(Refinery, 0, 0);
+    let report = world.flow_report(60.0);
+    assert_eq!(report.machines[refinery.index()].idle, 1.0);
+    assert_eq!(report.bottleneck, None);
+    assert!(
+        report
+            .to_string()
+            .ends_with("bottleneck: nothing's running")
+    );
+}
+
+#[test]
+fn reset_counters_keeps_the_items() {
+    let mut world = World::new();
+    let structures = line(&mut world, &[AirPump, Refinery, AbysalDoor]);
+    let mut simulation = Simulation::new(&world);
+    simulation.run(60.0);
+    simulation.reset_counters();
+    let report = simulation.report();
+    assert_eq!(report.seconds, 0.0);
+    assert!(report.machines.iter().all(|m| m.crafts == 0));
+    // already backed up, so the door has work from the very first tick
+    simulation.step();
+    assert_eq!(
+        simulation.report().machines[structures[2].index()].working,
+        1.0
+    );
+}